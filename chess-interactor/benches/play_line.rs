@@ -0,0 +1,27 @@
+use std::str::FromStr;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use chess_interactor::{Chess, ChessBoardPosition, GameOver, Move};
+
+/// The shortest win against the default black king strategy from a3 a1 e8.
+const MATING_LINE: [&str; 25] = [
+    "Qb1", "Qa1", "Qf6", "Qf8", "Qe7", "Qf7", "Qa2", "Qg8", "Qg7", "Qg6", "Qg5", "Qe3", "Qb3",
+    "Qf3", "Qh3", "Qh2", "Qh4", "Qg4", "Qf4", "Qe4", "Qf3", "Qe3", "Qf2", "Qe2", "Qd1#",
+];
+
+fn play_line(c: &mut Criterion) {
+    let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    let new_game = || Chess::new(pos("a3"), pos("a1"), pos("e8"), 50);
+    let moves: Vec<Move> = MATING_LINE.iter().map(|m| m.parse().unwrap()).collect();
+
+    // Make sure the whole line is played rather than cut short by a rejected move
+    let (outcome, transcript) = new_game().play_line(&moves);
+    assert!(matches!(outcome, GameOver::Checkmate));
+    assert_eq!(transcript.len(), MATING_LINE.len());
+
+    c.bench_function("play_line", |b| b.iter(|| new_game().play_line(&moves)));
+}
+
+criterion_group!(benches, play_line);
+criterion_main!(benches);
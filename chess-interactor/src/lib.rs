@@ -1,8 +1,20 @@
-use std::io::BufRead;
+use std::collections::HashMap;
+use std::io::{BufRead, Read, Write};
+use std::sync::{Arc, Mutex, PoisonError};
 
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The number of rows and columns of the chess board.
+///
+/// ```
+/// use chess_interactor::{ChessBoardPosition, BOARD_SIZE};
+/// let corner = ChessBoardPosition { row: BOARD_SIZE - 1, column: BOARD_SIZE - 1 };
+/// assert_eq!(corner.to_string(), "h8");
+/// ```
+pub const BOARD_SIZE: u8 = 8;
+const BOARD_LAST_INDEX: u8 = BOARD_SIZE - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ChessBoardPosition {
     pub row: u8,
     pub column: u8,
@@ -18,7 +30,7 @@ impl std::fmt::Display for ChessBoardPosition {
     /// assert_eq!(format!("{}", ChessBoardPosition::from_str("h8").unwrap()), "h8");
     /// ```
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}{}", (self.column + b'a') as char, self.row + 1)
+        write!(f, "{}{}", self.file_char(), self.rank_number())
     }
 }
 
@@ -43,21 +55,95 @@ impl std::str::FromStr for ChessBoardPosition {
             return Err("invalid length");
         }
         let s = s.as_bytes();
-        let column = if let column @ b'a'..=b'h' = s[0] {
-            column - b'a'
-        } else {
-            return Err("invalid column");
-        };
-        let row = if let row @ b'1'..=b'8' = s[1] {
-            row - b'1'
-        } else {
-            return Err("invalid row");
-        };
+        let column = Self::file_from_char(char::from(s[0])).ok_or("invalid column")?;
+        let row = Self::rank_from_char(char::from(s[1])).ok_or("invalid row")?;
         Ok(Self { row, column })
     }
 }
 
 impl ChessBoardPosition {
+    /// Parse a position written either as `a1` or with the rank first as `1a`.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::ChessBoardPosition;
+    /// let d2 = ChessBoardPosition::from_str("d2").unwrap();
+    /// assert_eq!(ChessBoardPosition::from_str_tolerant("d2"), Ok(d2));
+    /// assert_eq!(ChessBoardPosition::from_str_tolerant("2d"), Ok(d2));
+    /// assert_eq!(ChessBoardPosition::from_str_tolerant("2d").unwrap().to_string(), "d2");
+    /// assert_eq!(ChessBoardPosition::from_str("2d").unwrap_err(), "invalid column");
+    /// assert_eq!(ChessBoardPosition::from_str_tolerant("dd").unwrap_err(), "invalid row");
+    /// assert_eq!(ChessBoardPosition::from_str_tolerant("22").unwrap_err(), "invalid column");
+    /// assert_eq!(ChessBoardPosition::from_str_tolerant("2d3").unwrap_err(), "invalid length");
+    /// ```
+    pub fn from_str_tolerant(s: &str) -> Result<Self, &'static str> {
+        if s.len() != 2 {
+            return Err("invalid length");
+        }
+        let bytes = s.as_bytes();
+        if let (Some(row), Some(column)) = (
+            Self::rank_from_char(char::from(bytes[0])),
+            Self::file_from_char(char::from(bytes[1])),
+        ) {
+            return Ok(Self { row, column });
+        }
+        s.parse()
+    }
+
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::ChessBoardPosition;
+    /// let pos = ChessBoardPosition::from_str("d5").unwrap();
+    /// assert_eq!(pos.file_char(), 'd');
+    /// assert_eq!(ChessBoardPosition::from_str("a1").unwrap().file_char(), 'a');
+    /// assert_eq!(ChessBoardPosition::from_str("h8").unwrap().file_char(), 'h');
+    /// assert_eq!(format!("{}{}", pos.file_char(), pos.rank_number()), pos.to_string());
+    /// ```
+    pub fn file_char(&self) -> char {
+        char::from(b'a' + self.column)
+    }
+
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::ChessBoardPosition;
+    /// assert_eq!(ChessBoardPosition::from_str("d5").unwrap().rank_number(), 5);
+    /// assert_eq!(ChessBoardPosition::from_str("a1").unwrap().rank_number(), 1);
+    /// assert_eq!(ChessBoardPosition::from_str("h8").unwrap().rank_number(), 8);
+    /// ```
+    pub fn rank_number(&self) -> u8 {
+        self.row + 1
+    }
+
+    /// ```
+    /// use chess_interactor::ChessBoardPosition;
+    /// assert_eq!(ChessBoardPosition::file_from_char('a'), Some(0));
+    /// assert_eq!(ChessBoardPosition::file_from_char('h'), Some(7));
+    /// assert_eq!(ChessBoardPosition::file_from_char('i'), None);
+    /// assert_eq!(ChessBoardPosition::file_from_char('A'), None);
+    /// assert_eq!(ChessBoardPosition::file_from_char('1'), None);
+    /// ```
+    pub fn file_from_char(c: char) -> Option<u8> {
+        match c {
+            'a'..='h' => Some(c as u8 - b'a'),
+            _ => None,
+        }
+    }
+
+    /// ```
+    /// use chess_interactor::ChessBoardPosition;
+    /// assert_eq!(ChessBoardPosition::rank_from_char('1'), Some(0));
+    /// assert_eq!(ChessBoardPosition::rank_from_char('8'), Some(7));
+    /// assert_eq!(ChessBoardPosition::rank_from_char('0'), None);
+    /// assert_eq!(ChessBoardPosition::rank_from_char('9'), None);
+    /// assert_eq!(ChessBoardPosition::rank_from_char('a'), None);
+    /// ```
+    pub fn rank_from_char(c: char) -> Option<u8> {
+        match c {
+            '1'..='8' => Some(c as u8 - b'1'),
+            _ => None,
+        }
+    }
+
     /// return a distance to the rhs position which must be reachable by Queen allowed moves
     ///
     /// ```
@@ -74,6 +160,13 @@ impl ChessBoardPosition {
     /// assert!(pos.queen_distance(&ChessBoardPosition::from_str("c2").unwrap()).is_err());
     /// let pos = ChessBoardPosition::from_str("d4").unwrap();
     /// assert_eq!(pos.queen_distance(&ChessBoardPosition::from_str("a1").unwrap()), Ok((3, (-1, -1))));
+    /// let pos = ChessBoardPosition::from_str("h8").unwrap();
+    /// assert_eq!(pos.queen_distance(&ChessBoardPosition::from_str("a1").unwrap()), Ok((7, (-1, -1))));
+    /// assert_eq!(pos.queen_distance(&ChessBoardPosition::from_str("a8").unwrap()), Ok((7, (0, -1))));
+    /// assert_eq!(pos.queen_distance(&ChessBoardPosition::from_str("h1").unwrap()), Ok((7, (-1, 0))));
+    /// let pos = ChessBoardPosition::from_str("a8").unwrap();
+    /// assert_eq!(pos.queen_distance(&ChessBoardPosition::from_str("h1").unwrap()), Ok((7, (-1, 1))));
+    /// assert!(pos.queen_distance(&ChessBoardPosition::from_str("h2").unwrap()).is_err());
     /// ```
     pub fn queen_distance(&self, rhs: &Self) -> Result<(u8, (i8, i8)), &'static str> {
         let row_diff = i16::from(rhs.row) - i16::from(self.row);
@@ -91,20 +184,85 @@ impl ChessBoardPosition {
 
         Ok((
             distance,
-            (
-                (row_diff / i16::from(distance)) as i8,
-                (column_diff / i16::from(distance)) as i8,
-            ),
+            (row_diff.signum() as i8, column_diff.signum() as i8),
         ))
     }
+
+    /// Return the number of king moves needed to reach the rhs position.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::ChessBoardPosition;
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert_eq!(pos("a1").king_distance(&pos("a1")), 0);
+    /// assert_eq!(pos("a1").king_distance(&pos("b2")), 1);
+    /// assert_eq!(pos("a1").king_distance(&pos("c7")), 6);
+    /// assert_eq!(pos("h8").king_distance(&pos("e6")), 3);
+    /// ```
+    pub fn king_distance(&self, rhs: &Self) -> u8 {
+        std::cmp::max(
+            (i16::from(rhs.row) - i16::from(self.row)).abs(),
+            (i16::from(rhs.column) - i16::from(self.column)).abs(),
+        ) as u8
+    }
+
+    /// Return the number of king moves needed to reach the nearest corner.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::ChessBoardPosition;
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert_eq!(pos("a1").corner_distance(), 0);
+    /// assert_eq!(pos("h7").corner_distance(), 1);
+    /// assert_eq!(pos("c2").corner_distance(), 2);
+    /// assert_eq!(pos("e4").corner_distance(), 3);
+    /// ```
+    pub fn corner_distance(&self) -> u8 {
+        std::cmp::max(
+            std::cmp::min(self.row, BOARD_LAST_INDEX - self.row),
+            std::cmp::min(self.column, BOARD_LAST_INDEX - self.column),
+        )
+    }
+
+    /// return the positions strictly between self and the rhs position which must be reachable
+    /// by Queen allowed moves
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::ChessBoardPosition;
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert_eq!(pos("a1").squares_between(&pos("a1")), Ok(vec![]));
+    /// assert_eq!(pos("a1").squares_between(&pos("b2")), Ok(vec![]));
+    /// assert_eq!(pos("a1").squares_between(&pos("d4")), Ok(vec![pos("b2"), pos("c3")]));
+    /// assert_eq!(pos("h5").squares_between(&pos("e5")), Ok(vec![pos("g5"), pos("f5")]));
+    /// assert!(pos("a1").squares_between(&pos("b3")).is_err());
+    /// ```
+    pub fn squares_between(&self, rhs: &Self) -> Result<Vec<Self>, &'static str> {
+        let (distance, (row_direction, column_direction)) = self.queen_distance(rhs)?;
+        Ok((1..i16::from(distance))
+            .map(|step| Self {
+                row: (i16::from(self.row) + step * i16::from(row_direction)) as u8,
+                column: (i16::from(self.column) + step * i16::from(column_direction)) as u8,
+            })
+            .collect())
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChessPiece {
     King,
     Queen,
 }
 
+impl std::fmt::Display for ChessPiece {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::King => "K",
+            Self::Queen => "Q",
+        })
+    }
+}
+
 impl std::str::FromStr for ChessPiece {
     type Err = &'static str;
 
@@ -128,357 +286,4319 @@ impl std::str::FromStr for ChessPiece {
     }
 }
 
-pub struct Chess {
-    white_king_position: ChessBoardPosition,
-    white_queen_position: ChessBoardPosition,
-    black_king_position: ChessBoardPosition,
-    moves: u64,
-    moves_limit: u64,
+/// An optional hint between the piece letter and the target square (e.g. `Q1d5` or `Qad5`)
+/// which must match the source position of the moved piece.
+///
+/// ```
+/// use std::str::FromStr;
+/// use chess_interactor::{Chess, ChessBoardPosition, GameOver, WrongInputKind};
+/// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+/// // The queen moves from a8 to d5
+/// let play = |chess_move: &str| {
+///     let mut chess = Chess::new(pos("a1"), pos("a8"), pos("h3"), 50);
+///     chess.apply_white_and_respond(chess_move.parse().unwrap())
+/// };
+/// for mismatching in &["Q2d5", "Qbd5"] {
+///     assert!(matches!(
+///         play(mismatching),
+///         Err(GameOver::WrongInput { kind: WrongInputKind::IllegalMove, error_message, .. })
+///             if error_message == "disambiguation does not match the piece position"
+///     ));
+/// }
+/// for matching in &["Q8d5", "Qad5", "Qd5"] {
+///     assert!(play(matching).is_ok());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disambiguation {
+    Row(u8),
+    Column(u8),
 }
 
-#[derive(Debug)]
-pub enum GameOver {
-    WrongInput {
-        error_message: String,
-        input: String,
-    },
-    TooManyMoves,
-    Draw,
-    Stalemate,
+impl Disambiguation {
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{ChessBoardPosition, Disambiguation};
+    /// let pos = ChessBoardPosition::from_str("d1").unwrap();
+    /// assert!(Disambiguation::Row(0).matches(&pos));
+    /// assert!(Disambiguation::Column(3).matches(&pos));
+    /// assert!(!Disambiguation::Row(3).matches(&pos));
+    /// assert!(!Disambiguation::Column(0).matches(&pos));
+    /// ```
+    pub fn matches(&self, position: &ChessBoardPosition) -> bool {
+        match *self {
+            Self::Row(row) => position.row == row,
+            Self::Column(column) => position.column == column,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveClaim {
+    /// `+` suffix
+    Check,
+    /// `#` suffix
     Checkmate,
 }
 
-impl Chess {
-    pub fn new(
-        white_king_position: ChessBoardPosition,
-        white_queen_position: ChessBoardPosition,
-        black_kind_position: ChessBoardPosition,
-        moves_limit: u64,
-    ) -> Self {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub piece: ChessPiece,
+    pub disambiguation: Option<Disambiguation>,
+    pub to: ChessBoardPosition,
+    pub claim: Option<MoveClaim>,
+}
+
+impl Move {
+    /// Represent the black king reply as a move, which formats as the `K<square>` protocol line.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{ChessBoardPosition, ChessPiece, Move};
+    /// let reply = Move::black_king_reply(ChessBoardPosition::from_str("b8").unwrap());
+    /// assert_eq!(reply.piece, ChessPiece::King);
+    /// assert_eq!(reply.to_string(), "Kb8");
+    /// ```
+    pub fn black_king_reply(to: ChessBoardPosition) -> Self {
         Self {
-            white_king_position,
-            white_queen_position,
-            black_king_position: black_kind_position,
-            moves: 0,
-            moves_limit,
+            piece: ChessPiece::King,
+            disambiguation: None,
+            to,
+            claim: None,
         }
     }
+}
 
-    pub fn moves(&self) -> u64 {
-        self.moves
+impl std::fmt::Display for Move {
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::Move;
+    /// for chess_move in &["Qd5", "Kd5", "Qd5#", "Q1d5", "Qad5#", "Qd5+", "Qad5+"] {
+    ///     assert_eq!(Move::from_str(chess_move).unwrap().to_string(), *chess_move);
+    /// }
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.piece)?;
+        match self.disambiguation {
+            Some(Disambiguation::Row(row)) => write!(f, "{}", char::from(b'1' + row))?,
+            Some(Disambiguation::Column(column)) => write!(f, "{}", char::from(b'a' + column))?,
+            None => (),
+        }
+        write!(f, "{}", self.to)?;
+        match self.claim {
+            Some(MoveClaim::Check) => write!(f, "+")?,
+            Some(MoveClaim::Checkmate) => write!(f, "#")?,
+            None => (),
+        }
+        Ok(())
     }
+}
 
-    pub fn play(&mut self) -> GameOver {
-        let mut line = String::new();
-        let stdin = std::io::stdin();
-        let mut stdin = stdin.lock();
-        println!(
-            "{} {} {}",
-            self.white_king_position, self.white_queen_position, self.black_king_position
-        );
-        info!(target: "game_log", "{} {} {}", self.white_king_position, self.white_queen_position, self.black_king_position);
-        loop {
-            if self.moves >= self.moves_limit {
-                return GameOver::TooManyMoves;
-            }
-
-            line.clear();
-            if let Err(error) = stdin.read_line(&mut line) {
-                return GameOver::WrongInput {
-                    error_message: format!(
-                        "Reading a new line from a solution failed: {:?}",
-                        error
-                    ),
-                    input: "".into(),
-                };
-            }
-            let line = line.trim();
-            info!(target: "game_log", "{}", line);
-
-            let checkmate = if line.len() == 4 && line.ends_with("#") {
-                true
-            } else if line.len() == 3 {
-                false
-            } else {
-                return GameOver::WrongInput {
-                    error_message: "line is neither of length 3 nor length 4 with '#' at the end"
-                        .into(),
-                    input: line.into(),
-                };
-            };
+impl std::str::FromStr for Move {
+    type Err = &'static str;
 
-            let chess_piece: ChessPiece = match line[..1].parse() {
-                Ok(chess_piece) => chess_piece,
-                Err(err) => {
-                    return GameOver::WrongInput {
-                        error_message: err.into(),
-                        input: line.into(),
-                    };
-                }
-            };
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{ChessBoardPosition, ChessPiece, Disambiguation, Move, MoveClaim};
+    /// let d5 = ChessBoardPosition::from_str("d5").unwrap();
+    /// assert_eq!(
+    ///     Move::from_str("Qd5").unwrap(),
+    ///     Move { piece: ChessPiece::Queen, disambiguation: None, to: d5, claim: None }
+    /// );
+    /// assert_eq!(
+    ///     Move::from_str("Qd5#").unwrap(),
+    ///     Move { piece: ChessPiece::Queen, disambiguation: None, to: d5, claim: Some(MoveClaim::Checkmate) }
+    /// );
+    /// assert_eq!(Move::from_str("Q1d5").unwrap().disambiguation, Some(Disambiguation::Row(0)));
+    /// assert_eq!(Move::from_str("Qad5").unwrap().disambiguation, Some(Disambiguation::Column(0)));
+    /// assert_eq!(Move::from_str("Qad5#").unwrap().to, d5);
+    /// assert_eq!(Move::from_str("Qd5+").unwrap().claim, Some(MoveClaim::Check));
+    /// assert_eq!(Move::from_str("Q").unwrap_err(), "invalid move length");
+    /// assert_eq!(Move::from_str("Qa1d5").unwrap_err(), "move is too long");
+    /// assert_eq!(Move::from_str("Qa1d5#").unwrap_err(), "move is too long");
+    /// assert_eq!(Move::from_str("").unwrap_err(), "empty move");
+    /// assert_eq!(Move::from_str("Xd5").unwrap_err(), "invalid chess piece");
+    /// assert_eq!(Move::from_str("Qxd5").unwrap_err(), "invalid disambiguation");
+    /// assert_eq!(Move::from_str("Qd9").unwrap_err(), "invalid row");
+    /// assert_eq!(Move::from_str("Ы1").unwrap_err(), "invalid chess piece");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("empty move");
+        }
+        let (s, claim) = if let Some(s) = s.strip_suffix('#') {
+            (s, Some(MoveClaim::Checkmate))
+        } else if let Some(s) = s.strip_suffix('+') {
+            (s, Some(MoveClaim::Check))
+        } else {
+            (s, None)
+        };
+        if s.len() > 4 {
+            return Err("move is too long");
+        }
+        if s.len() < 3 {
+            return Err("invalid move length");
+        }
+        let piece = s.get(..1).ok_or("invalid chess piece")?.parse()?;
+        let disambiguation = if s.len() == 4 {
+            let hint = char::from(s.as_bytes()[1]);
+            Some(
+                if let Some(row) = ChessBoardPosition::rank_from_char(hint) {
+                    Disambiguation::Row(row)
+                } else if let Some(column) = ChessBoardPosition::file_from_char(hint) {
+                    Disambiguation::Column(column)
+                } else {
+                    return Err("invalid disambiguation");
+                },
+            )
+        } else {
+            None
+        };
+        let to = s.get(s.len() - 2..).ok_or("invalid length")?.parse()?;
+        Ok(Self {
+            piece,
+            disambiguation,
+            to,
+            claim,
+        })
+    }
+}
 
-            let chess_piece_move: ChessBoardPosition = match line[1..3].parse() {
-                Ok(chess_piece_move) => chess_piece_move,
-                Err(err) => {
-                    return GameOver::WrongInput {
-                        error_message: err.into(),
-                        input: line.into(),
-                    };
-                }
-            };
+/// The start position line of the protocol: white king, white queen and black king positions.
+///
+/// ```
+/// use std::str::FromStr;
+/// use chess_interactor::{ChessBoardPosition, InitialState};
+/// let initial_state = InitialState::from_str("a2 h8 a4").unwrap();
+/// assert_eq!(initial_state.white_king, ChessBoardPosition::from_str("a2").unwrap());
+/// assert_eq!(initial_state.white_queen, ChessBoardPosition::from_str("h8").unwrap());
+/// assert_eq!(initial_state.black_king, ChessBoardPosition::from_str("a4").unwrap());
+/// assert_eq!(initial_state.to_string(), "a2 h8 a4");
+/// assert_eq!(InitialState::from_str(" a2\th8\na4\n").unwrap(), initial_state);
+/// assert_eq!(InitialState::from_str("a2 h8").unwrap_err(), "unable to find the initial black king position");
+/// assert_eq!(InitialState::from_str("a2 h8 a4 b1").unwrap_err(), "too many initial positions");
+/// assert_eq!(InitialState::from_str("a2 h9 a4").unwrap_err(), "invalid row");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InitialState {
+    pub white_king: ChessBoardPosition,
+    pub white_queen: ChessBoardPosition,
+    pub black_king: ChessBoardPosition,
+}
 
-            if let Err(err) = self.try_apply_move(chess_piece, chess_piece_move) {
-                return GameOver::WrongInput {
-                    error_message: err.into(),
-                    input: line.into(),
-                };
-            }
-            self.moves += 1;
+impl std::fmt::Display for InitialState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.white_king, self.white_queen, self.black_king
+        )
+    }
+}
 
-            if self
-                .black_king_position
-                .queen_distance(&self.white_queen_position)
-                .map(|(distance, _)| distance)
-                .unwrap_or(0)
-                == 1
-                && self
-                    .white_king_position
-                    .queen_distance(&self.white_queen_position)
-                    .map(|(distance, _)| distance)
-                    .unwrap_or(0)
-                    != 1
-            {
-                debug!(
-                    "White queen moved too close to the black king without white king protection"
-                );
-                return GameOver::Draw;
-            }
+impl InitialState {
+    /// Format the positions with piece labels, e.g. `WK=a2 WQ=h8 BK=a4`.
+    ///
+    /// ```
+    /// use chess_interactor::InitialState;
+    /// let initial_state: InitialState = "e1 d1 e8".parse().unwrap();
+    /// assert_eq!(initial_state.to_labeled_string(), "WK=e1 WQ=d1 BK=e8");
+    /// ```
+    pub fn to_labeled_string(&self) -> String {
+        format!(
+            "WK={} WQ={} BK={}",
+            self.white_king, self.white_queen, self.black_king
+        )
+    }
+}
 
-            if let Err(game_over) = self.try_move_black_king() {
-                if let GameOver::Checkmate = game_over {
-                    if !checkmate {
-                        return GameOver::WrongInput {
-                            error_message: "no checkmate when expected".into(),
-                            input: line.into(),
-                        };
-                    }
-                }
-                return game_over;
-            };
+impl std::str::FromStr for InitialState {
+    type Err = &'static str;
 
-            println!("K{}", self.black_king_position);
-            info!(target: "game_log", "K{}", self.black_king_position);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut positions = s.split_ascii_whitespace();
+        let white_king = positions
+            .next()
+            .ok_or("unable to find the initial white king position")?
+            .parse()?;
+        let white_queen = positions
+            .next()
+            .ok_or("unable to find the initial white queen position")?
+            .parse()?;
+        let black_king = positions
+            .next()
+            .ok_or("unable to find the initial black king position")?
+            .parse()?;
+        if positions.next().is_some() {
+            return Err("too many initial positions");
         }
+        Ok(Self {
+            white_king,
+            white_queen,
+            black_king,
+        })
     }
+}
 
-    fn try_apply_move(
-        &mut self,
-        chess_piece: ChessPiece,
-        chess_piece_move: ChessBoardPosition,
-    ) -> Result<(), &'static str> {
-        match chess_piece {
-            #[cfg(not(feature = "king-moves-enabled"))]
-            ChessPiece::King => return Err("king moves are not allowed"),
-            #[cfg(feature = "king-moves-enabled")]
-            ChessPiece::King => {
-                debug!(
-                    "Tring to move white king from {} to {}",
-                    self.white_king_position, chess_piece_move
-                );
-                if self.white_queen_position == chess_piece_move {
-                    return Err("king tried to move over the queen");
-                }
-                let (distance, _) = self
-                    .white_king_position
-                    .queen_distance(&chess_piece_move)
-                    .map_err(|_| "king tried to do impossible move")?;
-                if distance == 0 {
-                    return Err("king was not moved");
-                }
-                if distance > 1 {
-                    return Err("king tried to move too far");
-                }
-                if let Ok((1, _)) = self.black_king_position.queen_distance(&chess_piece_move) {
-                    return Err("white king tried to move next to the black king");
-                }
-                self.white_king_position = chess_piece_move;
-            }
-            ChessPiece::Queen => {
-                debug!(
-                    "Tring to move white queen from {} to {}",
-                    self.white_queen_position, chess_piece_move
-                );
-                let (distance_to_new_position, direction_to_new_position) = self
-                    .white_queen_position
-                    .queen_distance(&chess_piece_move)
-                    .map_err(|_| "queen tried to do impossible move")?;
-                if distance_to_new_position == 0 {
-                    return Err("queen has not been moved");
-                }
+/// A solution move together with the black king reply to it, if the game continued.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptPly {
+    pub white_move: Move,
+    pub black_reply: Option<ChessBoardPosition>,
+    /// The FEN of the position after the ply, filled in by [`Chess::transcript`] and
+    /// [`Chess::play_line`].
+    pub resulting_fen: Option<String>,
+}
 
-                if let Ok((distance_to_white_king, direction_to_white_king)) = self
-                    .white_queen_position
-                    .queen_distance(&self.white_king_position)
-                {
-                    if direction_to_new_position == direction_to_white_king
-                        && distance_to_new_position >= distance_to_white_king
-                    {
-                        return Err("queen tried to jump over white king");
-                    }
-                }
+impl TranscriptPly {
+    /// Return the black king reply as a move, so both sides of the ply format alike.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{ChessBoardPosition, TranscriptPly};
+    /// let ply = TranscriptPly {
+    ///     white_move: "Qc1".parse().unwrap(),
+    ///     black_reply: Some(ChessBoardPosition::from_str("b8").unwrap()),
+    ///     resulting_fen: None,
+    /// };
+    /// assert_eq!(format!("{} {}", ply.white_move, ply.black_reply_move().unwrap()), "Qc1 Kb8");
+    /// ```
+    pub fn black_reply_move(&self) -> Option<Move> {
+        self.black_reply.map(Move::black_king_reply)
+    }
+}
 
-                if let Ok((distance_to_black_king, direction_to_black_king)) = self
-                    .white_queen_position
-                    .queen_distance(&self.black_king_position)
-                {
-                    if direction_to_new_position == direction_to_black_king
-                        && distance_to_new_position >= distance_to_black_king
-                    {
-                        return Err("queen tried to jump over black king");
-                    }
-                }
+/// The reason a recorded transcript does not match the rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The black king reply of the ply is not a legal move.
+    IllegalBlackReply { ply: usize },
+    /// The ply has no black king reply although the game continues.
+    MissingBlackReply { ply: usize },
+    /// The ply has a black king reply although the game is over.
+    UnexpectedBlackReply { ply: usize },
+    /// The ply follows the end of the game.
+    PlyAfterGameOver { ply: usize },
+    /// The transcript ends before the game is over.
+    Unfinished,
+}
 
-                self.white_queen_position = chess_piece_move;
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IllegalBlackReply { ply } => write!(f, "ply {}: illegal black king reply", ply),
+            Self::MissingBlackReply { ply } => write!(f, "ply {}: missing black king reply", ply),
+            Self::UnexpectedBlackReply { ply } => {
+                write!(f, "ply {}: black king reply after the game is over", ply)
             }
+            Self::PlyAfterGameOver { ply } => write!(f, "ply {}: the game is already over", ply),
+            Self::Unfinished => write!(f, "the transcript ends before the game is over"),
         }
-        Ok(())
     }
+}
 
-    fn try_move_black_king(&mut self) -> Result<(), GameOver> {
-        #[derive(Debug, Clone, Copy)]
-        enum ChessBoardCell {
-            Available,
-            King,
-            Attackable,
-        }
+impl std::error::Error for ValidationError {}
 
-        let mut board = [[ChessBoardCell::Available; 8]; 8];
+/// The reason a FEN string cannot be imported by [`Chess::from_fen`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenError {
+    /// The piece placement or the side to move field is missing.
+    TooFewFields,
+    /// The piece placement does not describe 8 ranks of 8 squares.
+    BadPiecePlacement,
+    /// The pieces are not exactly a white king, a white queen and a black king.
+    WrongPieceSet,
+    /// The side to move is not white.
+    BadSideToMove,
+}
 
-        // Mark attackable cells by white king
-        for row in usize::from(self.white_king_position.row.saturating_sub(1))
-            ..=usize::from(self.white_king_position.row + 1).min(7)
-        {
-            for column in usize::from(self.white_king_position.column.saturating_sub(1))
-                ..=usize::from(self.white_king_position.column + 1).min(7)
-            {
-                board[row][column] = ChessBoardCell::Attackable;
+impl std::fmt::Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::TooFewFields => "too few FEN fields",
+            Self::BadPiecePlacement => "malformed FEN piece placement",
+            Self::WrongPieceSet => {
+                "FEN position must have exactly a white king, a white queen and a black king"
             }
-        }
-        board[usize::from(self.white_king_position.row)]
-            [usize::from(self.white_king_position.column)] = ChessBoardCell::King;
+            Self::BadSideToMove => "FEN side to move must be white",
+        })
+    }
+}
 
-        // Mark attackable cells by white queen to the right
-        let row = usize::from(self.white_queen_position.row);
-        for column in (usize::from(self.white_queen_position.column) + 1)..=7 {
-            if let ChessBoardCell::King = board[row][column] {
-                break;
-            }
-            board[row][column] = ChessBoardCell::Attackable;
-        }
-        // Mark attackable cells by white queen to the left
-        for column in (0..usize::from(self.white_queen_position.column)).rev() {
-            if let ChessBoardCell::King = board[row][column] {
-                break;
-            }
-            board[row][column] = ChessBoardCell::Attackable;
-        }
+impl std::error::Error for FenError {}
 
-        // Mark attackable cells by white queen up
-        let column = usize::from(self.white_queen_position.column);
-        for row in (usize::from(self.white_queen_position.row) + 1)..=7 {
-            if let ChessBoardCell::King = board[row][column] {
-                break;
-            }
-            board[row][column] = ChessBoardCell::Attackable;
-        }
-        // Mark attackable cells by white queen down
-        for row in (0..usize::from(self.white_queen_position.row)).rev() {
-            if let ChessBoardCell::King = board[row][column] {
-                break;
-            }
-            board[row][column] = ChessBoardCell::Attackable;
-        }
+/// Identifies a position regardless of the move counters.
+///
+/// The solver is always the side to move between plies, so the three piece squares fully
+/// describe the position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PositionKey {
+    pub white_king_position: ChessBoardPosition,
+    pub white_queen_position: ChessBoardPosition,
+    pub black_king_position: ChessBoardPosition,
+}
 
-        // Mark attackable cells by white queen up right
-        let mut column = usize::from(self.white_queen_position.column);
-        let mut row = usize::from(self.white_queen_position.row);
-        while row < 7 && column < 7 {
-            column += 1;
-            row += 1;
-            if let ChessBoardCell::King = board[row][column] {
-                break;
+/// Defines how the black king chooses its reply to a white move.
+pub trait BlackKingStrategy {
+    /// Choose the new black king position out of the non-empty list of legal `candidates`.
+    ///
+    /// The returned position must be one of the `candidates`.
+    fn choose(&self, chess: &Chess, candidates: &[ChessBoardPosition]) -> ChessBoardPosition;
+}
+
+/// Picks the last legal reply scanning the board from a1 to h8.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NaiveStrategy;
+
+impl BlackKingStrategy for NaiveStrategy {
+    fn choose(&self, _chess: &Chess, candidates: &[ChessBoardPosition]) -> ChessBoardPosition {
+        candidates[candidates.len() - 1]
+    }
+}
+
+/// Picks the reply which leaves the black king with the most legal moves.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GreedyStrategy;
+
+impl BlackKingStrategy for GreedyStrategy {
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{BlackKingStrategy, Chess, ChessBoardPosition, GreedyStrategy};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a1"), pos("b3"), pos("c7"), 50);
+    /// let candidates: Vec<_> = chess.black_reply_candidates().into_iter().map(|(_, to)| to).collect();
+    /// assert_eq!(candidates, [pos("c6"), pos("d6"), pos("d7"), pos("c8"), pos("d8")]);
+    /// assert_eq!(GreedyStrategy.choose(&chess, &candidates), pos("d7"));
+    /// ```
+    fn choose(&self, chess: &Chess, candidates: &[ChessBoardPosition]) -> ChessBoardPosition {
+        let board = chess.attack_map();
+        let score = |candidate| {
+            let reply = Chess {
+                black_king_position: candidate,
+                ..chess.clone()
+            };
+            (
+                reply.black_king_moves(&board).len(),
+                chess.config.prefer_opposition && reply.in_opposition(),
+            )
+        };
+        let mut best_candidate = candidates[0];
+        let mut best_score = score(best_candidate);
+        for &candidate in &candidates[1..] {
+            let candidate_score = score(candidate);
+            if candidate_score > best_score {
+                best_candidate = candidate;
+                best_score = candidate_score;
+            }
+        }
+        best_candidate
+    }
+}
+
+/// Decides whether a white move is legal in the given position, so problem variants with
+/// different rules can reuse the game engine.
+///
+/// The positions and the moves limit are still checked by the game itself, and the white king
+/// moves are only validated with the `king-moves-enabled` feature.
+///
+/// ```
+/// use std::str::FromStr;
+/// use chess_interactor::{Chess, ChessBoardPosition, Move, MovePolicy, PositionKey};
+///
+/// /// Lets the queen jump to any empty square
+/// struct Permissive;
+///
+/// impl MovePolicy for Permissive {
+///     fn validate_queen_move(
+///         &self,
+///         position: &PositionKey,
+///         to: ChessBoardPosition,
+///     ) -> Result<(), &'static str> {
+///         if to == position.white_king_position || to == position.black_king_position {
+///             return Err("queen tried to move to an occupied square");
+///         }
+///         Ok(())
+///     }
+///
+///     fn validate_king_move(
+///         &self,
+///         _position: &PositionKey,
+///         _to: ChessBoardPosition,
+///     ) -> Result<(), &'static str> {
+///         Err("king moves are not allowed")
+///     }
+/// }
+///
+/// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+/// let mut chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+/// let moves: Vec<Move> = ["Qb3", "Qa4"].iter().map(|m| m.parse().unwrap()).collect();
+/// assert_eq!(chess.dry_run(&moves[..1]), vec![Err("queen tried to do impossible move".to_owned())]);
+/// chess.set_policy(Permissive);
+/// assert_eq!(
+///     chess.dry_run(&moves),
+///     vec![Ok(()), Err("queen tried to move to an occupied square".to_owned())]
+/// );
+/// ```
+pub trait MovePolicy {
+    /// Check the move of the white queen from its current square in `position` to `to`.
+    fn validate_queen_move(
+        &self,
+        position: &PositionKey,
+        to: ChessBoardPosition,
+    ) -> Result<(), &'static str>;
+
+    /// Check the move of the white king from its current square in `position` to `to`.
+    fn validate_king_move(
+        &self,
+        position: &PositionKey,
+        to: ChessBoardPosition,
+    ) -> Result<(), &'static str>;
+}
+
+/// The rules of the regular chess: the queen moves along the lines without jumping over the
+/// kings, and the white king moves to a neighbouring square not next to the black king.
+///
+/// ```
+/// use std::str::FromStr;
+/// use chess_interactor::{Chess, ChessBoardPosition, Move};
+/// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+/// let chess = Chess::new(pos("a7"), pos("a4"), pos("e4"), 50);
+/// let queen_move = |m: &str| chess.dry_run(&[Move::from_str(m).unwrap()]).remove(0);
+/// // Sliding up to the black king is fine, the hanging queen is the game's business
+/// assert_eq!(queen_move("Qd4"), Ok(()));
+/// assert_eq!(queen_move("Qe4"), Err("queen tried to move onto black king".to_owned()));
+/// assert_eq!(queen_move("Qf4"), Err("queen tried to jump over black king".to_owned()));
+/// assert_eq!(queen_move("Qa6"), Ok(()));
+/// assert_eq!(queen_move("Qa7"), Err("queen tried to move onto white king".to_owned()));
+/// assert_eq!(queen_move("Qa8"), Err("queen tried to jump over white king".to_owned()));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StandardPolicy;
+
+impl MovePolicy for StandardPolicy {
+    fn validate_queen_move(
+        &self,
+        position: &PositionKey,
+        to: ChessBoardPosition,
+    ) -> Result<(), &'static str> {
+        let (distance_to_new_position, direction_to_new_position) = position
+            .white_queen_position
+            .queen_distance(&to)
+            .map_err(|_| "queen tried to do impossible move")?;
+        if distance_to_new_position == 0 {
+            return Err("piece was not moved");
+        }
+
+        if let Ok((distance_to_white_king, direction_to_white_king)) = position
+            .white_queen_position
+            .queen_distance(&position.white_king_position)
+        {
+            if direction_to_new_position == direction_to_white_king {
+                match distance_to_new_position.cmp(&distance_to_white_king) {
+                    std::cmp::Ordering::Less => {}
+                    std::cmp::Ordering::Equal => {
+                        return Err("queen tried to move onto white king");
+                    }
+                    std::cmp::Ordering::Greater => {
+                        return Err("queen tried to jump over white king");
+                    }
+                }
+            }
+        }
+
+        if let Ok((distance_to_black_king, direction_to_black_king)) = position
+            .white_queen_position
+            .queen_distance(&position.black_king_position)
+        {
+            if direction_to_new_position == direction_to_black_king {
+                match distance_to_new_position.cmp(&distance_to_black_king) {
+                    std::cmp::Ordering::Less => {}
+                    std::cmp::Ordering::Equal => {
+                        return Err("queen tried to move onto black king");
+                    }
+                    std::cmp::Ordering::Greater => {
+                        return Err("queen tried to jump over black king");
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_king_move(
+        &self,
+        position: &PositionKey,
+        to: ChessBoardPosition,
+    ) -> Result<(), &'static str> {
+        if position.white_queen_position == to {
+            return Err("king tried to move over the queen");
+        }
+        let (distance, _) = position
+            .white_king_position
+            .queen_distance(&to)
+            .map_err(|_| "king tried to do impossible move")?;
+        if distance == 0 {
+            return Err("piece was not moved");
+        }
+        if distance > 1 {
+            return Err("king tried to move too far");
+        }
+        if let Ok((1, _)) = position.black_king_position.queen_distance(&to) {
+            return Err("white king tried to move next to the black king");
+        }
+        Ok(())
+    }
+}
+
+/// Renders a board position into an image (or any other byte representation), e.g. to show the
+/// final position of a game.
+pub trait BoardRenderer {
+    fn render(&self, chess: &Chess) -> Vec<u8>;
+}
+
+/// Renders the position as the ASCII diagram of [`Chess::render_ascii`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextRenderer;
+
+impl BoardRenderer for TextRenderer {
+    fn render(&self, chess: &Chess) -> Vec<u8> {
+        chess.render_ascii().into_bytes()
+    }
+}
+
+/// The unit in which the moves limit of a game is counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "config-file",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum MovesLimitUnit {
+    /// Count the solution (white) moves only.
+    #[default]
+    Moves,
+    /// Count the solution moves and the black king replies.
+    Plies,
+}
+
+/// Optional rules and protocol settings of a game.
+///
+/// ```
+/// use std::str::FromStr;
+/// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, GameOver, MovesLimitUnit};
+/// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+/// let input = b"Qb1\nQa2\nQb1\nQa2\nQb1\nQa2\nQb1\nQa2\nQb1\nQa2\nQb1\n";
+///
+/// let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 20);
+/// assert!(matches!(chess.play_with(&input[..], Vec::new()), GameOver::SolverDisconnected));
+/// assert_eq!(chess.moves(), 11);
+///
+/// let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 20);
+/// chess.set_config(GameConfig {
+///     moves_limit_unit: MovesLimitUnit::Plies,
+///     ..GameConfig::default()
+/// });
+/// assert!(matches!(chess.play_with(&input[..], Vec::new()), GameOver::TooManyMoves));
+/// assert_eq!(chess.moves(), 10);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "config-file",
+    derive(serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct GameConfig {
+    pub moves_limit_unit: MovesLimitUnit,
+    /// Log the FEN of the position after every ply with the `game_fen` tracing target.
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use std::str::FromStr;
+    /// use std::sync::{Arc, Mutex};
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, GameOver};
+    ///
+    /// #[derive(Clone)]
+    /// struct Logs(Arc<Mutex<Vec<u8>>>);
+    /// impl Write for Logs {
+    ///     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    ///         self.0.lock().unwrap().write(buf)
+    ///     }
+    ///     fn flush(&mut self) -> std::io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// chess.set_config(GameConfig { log_fen: true, ..GameConfig::default() });
+    /// let logs = Logs(Arc::new(Mutex::new(Vec::new())));
+    /// let subscriber = tracing_subscriber::fmt()
+    ///     .with_writer({
+    ///         let logs = logs.clone();
+    ///         move || logs.clone()
+    ///     })
+    ///     .with_ansi(false)
+    ///     .finish();
+    /// let game_over = tracing::subscriber::with_default(subscriber, || {
+    ///     chess.play_with(&b"Qc1\nQc7\nQa7#\n"[..], Vec::new())
+    /// });
+    /// assert!(matches!(game_over, GameOver::Checkmate));
+    /// let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    /// let fens: Vec<&str> = logs.lines().filter(|line| line.contains("game_fen")).collect();
+    /// // 3 solution moves and 2 black king replies
+    /// assert_eq!(fens.len(), 5);
+    /// assert!(fens[0].ends_with("k7/8/1K6/8/8/8/8/2Q5 b - - 1 1"));
+    /// assert!(fens[4].ends_with("k7/Q7/1K6/8/8/8/8/8 b - - 5 3"));
+    /// ```
+    pub log_fen: bool,
+    /// Require every checking move to be announced with `+` (or `#` on checkmate) and reject
+    /// `+` on moves that do not give check.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let play = |input: &[u8]| {
+    ///     let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 50);
+    ///     chess.set_config(GameConfig { require_check_announcement: true, ..GameConfig::default() });
+    ///     chess.play_with(input, Vec::new())
+    /// };
+    /// assert!(matches!(play(b"Qh2+\n"), GameOver::SolverDisconnected));
+    /// assert!(matches!(
+    ///     play(b"Qh2\n"),
+    ///     GameOver::WrongInput { kind: WrongInputKind::FalseClaim, .. }
+    /// ));
+    /// assert!(matches!(
+    ///     play(b"Qb1+\n"),
+    ///     GameOver::WrongInput { kind: WrongInputKind::FalseClaim, .. }
+    /// ));
+    /// ```
+    pub require_check_announcement: bool,
+    /// Accept several whitespace-separated moves on one input line. The moves are played one
+    /// after another with a black king reply to each of them. If any of the moves cannot be
+    /// parsed or is rejected, including a move after the end of the game, the whole line is
+    /// rejected without playing or answering any of its moves.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let play = |input: &[u8]| {
+    ///     let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    ///     chess.set_config(GameConfig { multiple_moves_per_line: true, ..GameConfig::default() });
+    ///     let mut output = Vec::new();
+    ///     let game_over = chess.play_with(input, &mut output);
+    ///     (game_over, String::from_utf8(output).unwrap())
+    /// };
+    ///
+    /// let (game_over, output) = play(b"Qc1 Qc7\nQa7#\n");
+    /// assert!(matches!(game_over, GameOver::Checkmate));
+    /// assert_eq!(output, "b6 c2 a8\nKb8\nKa8\n");
+    ///
+    /// let (game_over, output) = play(b"Qc1 Qc9\n");
+    /// assert!(matches!(game_over, GameOver::WrongInput { kind: WrongInputKind::Unparseable, .. }));
+    /// assert_eq!(output, "b6 c2 a8\n");
+    ///
+    /// // Qc1 is legal, but the queen cannot get from c1 to d3, so Qc1 is taken back unanswered
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// chess.set_config(GameConfig { multiple_moves_per_line: true, ..GameConfig::default() });
+    /// let mut output = Vec::new();
+    /// assert!(matches!(
+    ///     chess.play_with(&b"Qc1 Qd3\n"[..], &mut output),
+    ///     GameOver::WrongInput { kind: WrongInputKind::IllegalMove, .. }
+    /// ));
+    /// assert_eq!(output, b"b6 c2 a8\n");
+    /// assert_eq!(chess.moves(), 0);
+    /// assert_eq!(chess.initial_state().to_string(), "b6 c2 a8");
+    ///
+    /// let (game_over, output) = play(b"Qc1 Qc7 Qa7# Qb7\n");
+    /// assert!(matches!(
+    ///     game_over,
+    ///     GameOver::WrongInput { kind: WrongInputKind::IllegalMove, error_message, input }
+    ///         if error_message == "game is already over" && input == "Qb7"
+    /// ));
+    /// assert_eq!(output, "b6 c2 a8\n");
+    ///
+    /// let (game_over, output) = play(b"Qc1 Qc7 Qa7#\n");
+    /// assert!(matches!(game_over, GameOver::Checkmate));
+    /// assert_eq!(output, "b6 c2 a8\nKb8\nKa8\n");
+    /// ```
+    pub multiple_moves_per_line: bool,
+    /// Expect the solution to send the `handshake_token` line before its first move.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let play = |expect_handshake, input: &[u8]| {
+    ///     let mut chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    ///     chess.set_config(GameConfig { expect_handshake, ..GameConfig::default() });
+    ///     chess.play_with(input, Vec::new())
+    /// };
+    /// assert!(matches!(play(true, b"ready\nQc8#\n"), GameOver::Checkmate));
+    /// assert!(matches!(play(false, b"Qc8#\n"), GameOver::Checkmate));
+    /// assert!(matches!(
+    ///     play(true, b"Qc8#\n"),
+    ///     GameOver::WrongInput { kind: WrongInputKind::Unparseable, .. }
+    /// ));
+    /// assert!(matches!(
+    ///     play(false, b"ready\nQc8#\n"),
+    ///     GameOver::WrongInput { kind: WrongInputKind::Unparseable, .. }
+    /// ));
+    /// assert!(matches!(play(true, b""), GameOver::SolverDisconnected));
+    /// ```
+    pub expect_handshake: bool,
+    /// The handshake line expected when `expect_handshake` is set, `ready` by default.
+    pub handshake_token: String,
+    /// Expect the solution to echo the initial line it was given before its first move (and
+    /// before the handshake, if any).
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let play = |input: &[u8]| {
+    ///     let mut chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    ///     chess.set_config(GameConfig { expect_echo_start: true, ..GameConfig::default() });
+    ///     chess.play_with(input, Vec::new())
+    /// };
+    /// assert!(matches!(play(b"b6 c1 a8\nQc8#\n"), GameOver::Checkmate));
+    /// match play(b"b6 c2 a8\nQc8#\n") {
+    ///     GameOver::WrongInput { kind: WrongInputKind::Unparseable, error_message, input } => {
+    ///         assert_eq!(error_message, "expected the echo of the initial line \"b6 c1 a8\"");
+    ///         assert_eq!(input, "b6 c2 a8");
+    ///     }
+    ///     _ => panic!("the mismatching echo must be rejected"),
+    /// }
+    /// assert!(matches!(
+    ///     play(b"Qc8#\n"),
+    ///     GameOver::WrongInput { kind: WrongInputKind::Unparseable, .. }
+    /// ));
+    /// assert!(matches!(play(b""), GameOver::SolverDisconnected));
+    /// ```
+    pub expect_echo_start: bool,
+    /// Print the initial positions with piece labels (`WK=e1 WQ=d1 BK=e8`) instead of the bare
+    /// `e1 d1 e8` of the default protocol.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let initial_line = |labeled_initial_state| {
+    ///     let mut chess = Chess::new(pos("e1"), pos("d1"), pos("e8"), 50);
+    ///     chess.set_config(GameConfig { labeled_initial_state, ..GameConfig::default() });
+    ///     let mut output = Vec::new();
+    ///     chess.play_with(&b""[..], &mut output);
+    ///     String::from_utf8(output).unwrap()
+    /// };
+    /// assert_eq!(initial_line(false), "e1 d1 e8\n");
+    /// assert_eq!(initial_line(true), "WK=e1 WQ=d1 BK=e8\n");
+    /// ```
+    pub labeled_initial_state: bool,
+    /// Charge a stalemating move to the solution as an illegal move instead of ending the game
+    /// with [`GameOver::Stalemate`].
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let play = |forbid_allowing_stalemate| {
+    ///     let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    ///     chess.set_config(GameConfig { forbid_allowing_stalemate, ..GameConfig::default() });
+    ///     chess.play_with(&b"Qc7\n"[..], Vec::new())
+    /// };
+    /// assert!(matches!(play(false), GameOver::Stalemate));
+    /// match play(true) {
+    ///     GameOver::WrongInput { kind: WrongInputKind::IllegalMove, error_message, .. } => {
+    ///         assert_eq!(error_message, "move allowed stalemate");
+    ///     }
+    ///     other => panic!("unexpected outcome: {:?}", other),
+    /// }
+    /// ```
+    pub forbid_allowing_stalemate: bool,
+    /// Debugging aid, never use it for grading: log the moves which would end the game with
+    /// [`GameOver::WrongInput`] and wait for another move as if they were not sent.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, GameOver};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    /// chess.set_config(GameConfig { lenient_diagnostics: true, ..GameConfig::default() });
+    /// let mut output = Vec::new();
+    /// let game_over = chess.play_with(&b"Qd3\nhello\nQc8\nQc8#\n"[..], &mut output);
+    /// assert!(matches!(game_over, GameOver::Checkmate));
+    /// assert_eq!(chess.moves(), 1);
+    /// assert_eq!(output, b"b6 c1 a8\n");
+    /// ```
+    pub lenient_diagnostics: bool,
+    /// The number of unparseable lines ignored before a move is parsed, counted anew for every
+    /// move. Well-formed but illegal moves still end the game.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let play = |input: &[u8]| {
+    ///     let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    ///     chess.set_config(GameConfig { parse_retries: 1, ..GameConfig::default() });
+    ///     chess.play_with(input, Vec::new())
+    /// };
+    /// assert!(matches!(play(b"Qc\nQc1\noops\nQc7\nQa7#\n"), GameOver::Checkmate));
+    /// assert!(matches!(
+    ///     play(b"Qc\noops\nQc1\n"),
+    ///     GameOver::WrongInput { kind: WrongInputKind::Unparseable, .. }
+    /// ));
+    /// assert!(matches!(
+    ///     play(b"Qd4\n"),
+    ///     GameOver::WrongInput { kind: WrongInputKind::IllegalMove, .. }
+    /// ));
+    /// ```
+    pub parse_retries: u8,
+    /// Append the number of the remaining solution moves to the initial positions and to every
+    /// black king reply, e.g. `Kb8 49`.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// chess.set_config(GameConfig { print_moves_remaining: true, ..GameConfig::default() });
+    /// let mut output = Vec::new();
+    /// chess.play_with(&b"Qc1\nQc7\nQa7#\n"[..], &mut output);
+    /// assert_eq!(output, b"b6 c2 a8 50\nKb8 49\nKa8 48\n");
+    /// ```
+    pub print_moves_remaining: bool,
+    /// Additionally write the game to this log in the Testlib interactor format: the lines sent
+    /// to the solution prefixed with `>`, the lines read from it prefixed with `<`, and the
+    /// verdict token with the summary of the game when it is [finished](Chess::finish).
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use std::str::FromStr;
+    /// use std::sync::{Arc, Mutex};
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, GameOver, TestlibLog};
+    ///
+    /// #[derive(Clone, Default)]
+    /// struct Log(Arc<Mutex<Vec<u8>>>);
+    /// impl Write for Log {
+    ///     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    ///         self.0.lock().unwrap().write(buf)
+    ///     }
+    ///     fn flush(&mut self) -> std::io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let play = |input: &[u8]| {
+    ///     let log = Log::default();
+    ///     let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    ///     chess.set_config(GameConfig {
+    ///         testlib_log: Some(TestlibLog::new(log.clone())),
+    ///         ..GameConfig::default()
+    ///     });
+    ///     let outcome = chess.play_with(input, Vec::new());
+    ///     chess.finish(outcome);
+    ///     let log = log.0.lock().unwrap().clone();
+    ///     String::from_utf8(log).unwrap()
+    /// };
+    /// assert_eq!(
+    ///     play(b"Qc1\nQc7\nQa7#\n"),
+    ///     "> b6 c2 a8\n< Qc1\n> Kb8\n< Qc7\n> Ka8\n< Qa7#\n_ok Checkmate in 3 moves\n"
+    /// );
+    /// assert!(play(b"Qd4\n").ends_with("< Qd4\n_wa Rejected at move 1: queen tried to do impossible move\n"));
+    /// assert!(play(b"hello\n").lines().last().unwrap().starts_with("_pe "));
+    ///
+    /// // A broken log does not stop the game
+    /// struct Broken;
+    /// impl Write for Broken {
+    ///     fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+    ///         Err(std::io::Error::new(std::io::ErrorKind::Other, "disk full"))
+    ///     }
+    ///     fn flush(&mut self) -> std::io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// chess.set_config(GameConfig { testlib_log: Some(TestlibLog::new(Broken)), ..GameConfig::default() });
+    /// let outcome = chess.play_with(&b"Qc1\nQc7\nQa7#\n"[..], Vec::new());
+    /// assert!(matches!(outcome, GameOver::Checkmate));
+    /// assert!(chess.finish(outcome).solver_succeeded);
+    /// ```
+    #[cfg_attr(feature = "config-file", serde(skip))]
+    pub testlib_log: Option<TestlibLog>,
+    /// Let the [`GreedyStrategy`] break the ties between the replies leaving the black king
+    /// equally mobile in favour of the ones taking the opposition, see [`Chess::in_opposition`].
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{BlackKingStrategy, Chess, ChessBoardPosition, GameConfig, GreedyStrategy};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("a2"), pos("a5"), pos("c1"), 50);
+    /// let candidates = [pos("d1"), pos("c2")];
+    /// assert_eq!(GreedyStrategy.choose(&chess, &candidates), pos("d1"));
+    /// chess.set_config(GameConfig { prefer_opposition: true, ..GameConfig::default() });
+    /// assert_eq!(GreedyStrategy.choose(&chess, &candidates), pos("c2"));
+    /// ```
+    pub prefer_opposition: bool,
+    /// Count a [`GameOver::Stalemate`] as a success of the solution, like a checkmate.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, Color, GameConfig};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let stalemate = |stalemate_counts_as_win| {
+    ///     let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    ///     chess.set_config(GameConfig { stalemate_counts_as_win, ..GameConfig::default() });
+    ///     let outcome = chess.play_with(&b"Qc7\n"[..], Vec::new());
+    ///     chess.finish(outcome)
+    /// };
+    /// let result = stalemate(false);
+    /// assert!(!result.solver_succeeded);
+    /// assert_eq!(result.winner(), None);
+    /// let result = stalemate(true);
+    /// assert!(result.solver_succeeded);
+    /// assert_eq!(result.winner(), Some(Color::White));
+    /// assert_eq!(result.to_string(), "Stalemate after 1 move");
+    /// ```
+    pub stalemate_counts_as_win: bool,
+    /// Record every ply of the game together with the resulting FEN, see [`Chess::transcript`].
+    pub record_transcript: bool,
+    /// The longest accepted solution line in bytes, not counting the trailing `\n`, so a solution
+    /// cannot make the interactor buffer unbounded input. 4 KiB by default.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let play = |input: &[u8]| {
+    ///     let mut chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    ///     chess.set_config(GameConfig { max_line_bytes: 4, ..GameConfig::default() });
+    ///     chess.play_with(input, Vec::new())
+    /// };
+    /// assert!(matches!(play(b"Qc8#\n"), GameOver::Checkmate));
+    /// assert!(matches!(play(b"Qc8#"), GameOver::Checkmate));
+    /// match play(b"Qc8# \n") {
+    ///     GameOver::WrongInput { kind: WrongInputKind::Unparseable, error_message, input } => {
+    ///         assert_eq!(error_message, "input line too long");
+    ///         assert_eq!(input, "Qc8# ...");
+    ///     }
+    ///     _ => panic!("the oversized line must be rejected"),
+    /// }
+    ///
+    /// let mut chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    /// let huge_line = vec![b'Q'; 1 << 20];
+    /// assert!(matches!(
+    ///     chess.play_with(&huge_line[..], Vec::new()),
+    ///     GameOver::WrongInput { error_message, .. } if error_message == "input line too long"
+    /// ));
+    /// ```
+    pub max_line_bytes: usize,
+}
+
+/// A writer shared by the copies of a game receiving its Testlib-style log, see
+/// [`GameConfig::testlib_log`].
+///
+/// The log is a side channel, so failing to write it is reported as a warning and does not
+/// affect the game.
+#[derive(Clone)]
+pub struct TestlibLog(Arc<Mutex<dyn Write + Send>>);
+
+impl TestlibLog {
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Self {
+        Self(Arc::new(Mutex::new(writer)))
+    }
+
+    fn write_line(&self, line: std::fmt::Arguments) {
+        let mut writer = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Err(error) = writeln!(writer, "{}", line).and_then(|()| writer.flush()) {
+            warn!("Failed writing to the testlib log: {}", error);
+        }
+    }
+}
+
+impl std::fmt::Debug for TestlibLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("TestlibLog")
+    }
+}
+
+/// All the settings of a problem read from a single JSON file.
+///
+/// Only `initial_state` is required; `moves_limit` defaults to 50, `strategy` to `naive` and
+/// `config` takes the [`GameConfig`] fields by name. The optional `king_moves` flag must match
+/// the `king-moves-enabled` feature the interactor was built with.
+///
+/// ```
+/// # #[cfg(feature = "config-file")]
+/// # {
+/// use chess_interactor::{ConfigError, GameOver, MovesLimitUnit, ProblemConfig};
+/// let problem = ProblemConfig::from_reader(
+///     r#"{
+///         "initial_state": "b6 c2 a8",
+///         "moves_limit": 3,
+///         "strategy": "greedy",
+///         "config": {
+///             "moves_limit_unit": "plies",
+///             "require_check_announcement": true,
+///             "labeled_initial_state": true,
+///             "parse_retries": 2
+///         }
+///     }"#
+///     .as_bytes(),
+/// )
+/// .unwrap();
+/// assert_eq!(problem.initial_state.to_string(), "b6 c2 a8");
+/// assert_eq!(problem.moves_limit, 3);
+/// assert_eq!(problem.config.moves_limit_unit, MovesLimitUnit::Plies);
+/// assert_eq!(problem.config.parse_retries, 2);
+/// assert_eq!(problem.config.handshake_token, "ready");
+/// let mut chess = problem.into_chess().unwrap();
+/// let mut output = Vec::new();
+/// assert!(matches!(
+///     chess.play_with(&b"Qc1\nQc7\nQa7#\n"[..], &mut output),
+///     GameOver::WrongInput { error_message, .. } if error_message == "check was not announced"
+/// ));
+/// assert_eq!(output, b"WK=b6 WQ=c2 BK=a8\nKb8\n");
+///
+/// let problem = ProblemConfig::from_reader(&br#"{"initial_state": "a2 h8 a4"}"#[..]).unwrap();
+/// assert_eq!(problem.moves_limit, 50);
+/// assert_eq!(problem.strategy, "naive");
+///
+/// let error = |json: &str| ProblemConfig::from_reader(json.as_bytes()).err().unwrap();
+/// assert!(matches!(error(r#"{"initial_state": "a2 h9 a4"}"#), ConfigError::InitialState("invalid row")));
+/// assert!(matches!(error(r#"{"initial_state": "a2 h8 a4", "config": {"typo": 1}}"#), ConfigError::Malformed(_)));
+/// let king_moves = cfg!(feature = "king-moves-enabled");
+/// let with_king_moves = |king_moves| {
+///     format!(r#"{{"initial_state": "a2 h8 a4", "king_moves": {}}}"#, king_moves)
+/// };
+/// assert!(ProblemConfig::from_reader(with_king_moves(king_moves).as_bytes()).is_ok());
+/// assert!(matches!(error(&with_king_moves(!king_moves)), ConfigError::KingMovesMismatch));
+/// assert!(matches!(
+///     ProblemConfig::from_reader(&br#"{"initial_state": "a2 h8 a4", "strategy": "optimal"}"#[..])
+///         .unwrap()
+///         .into_chess(),
+///     Err(ConfigError::UnknownStrategy)
+/// ));
+/// assert!(matches!(
+///     ProblemConfig::from_reader(&br#"{"initial_state": "a2 h8 a4", "moves_limit": 0}"#[..])
+///         .unwrap()
+///         .into_chess(),
+///     Err(ConfigError::ZeroMovesLimit)
+/// ));
+/// # }
+/// ```
+#[cfg(feature = "config-file")]
+#[derive(Debug, Clone)]
+pub struct ProblemConfig {
+    pub initial_state: InitialState,
+    pub moves_limit: u64,
+    /// One of the names accepted by [`Chess::set_strategy_by_name`].
+    pub strategy: String,
+    pub config: GameConfig,
+}
+
+#[cfg(feature = "config-file")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProblemConfigFile {
+    initial_state: String,
+    #[serde(default = "ProblemConfigFile::default_moves_limit")]
+    moves_limit: u64,
+    #[serde(default = "ProblemConfigFile::default_strategy")]
+    strategy: String,
+    king_moves: Option<bool>,
+    #[serde(default)]
+    config: GameConfig,
+}
+
+#[cfg(feature = "config-file")]
+impl ProblemConfigFile {
+    fn default_moves_limit() -> u64 {
+        50
+    }
+
+    fn default_strategy() -> String {
+        "naive".into()
+    }
+}
+
+#[cfg(feature = "config-file")]
+impl ProblemConfig {
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, ConfigError> {
+        let file: ProblemConfigFile =
+            serde_json::from_reader(reader).map_err(ConfigError::Malformed)?;
+        if let Some(king_moves) = file.king_moves {
+            if king_moves != cfg!(feature = "king-moves-enabled") {
+                return Err(ConfigError::KingMovesMismatch);
+            }
+        }
+        Ok(Self {
+            initial_state: file
+                .initial_state
+                .parse()
+                .map_err(ConfigError::InitialState)?,
+            moves_limit: file.moves_limit,
+            strategy: file.strategy,
+            config: file.config,
+        })
+    }
+
+    /// Set up the game described by the config.
+    pub fn into_chess(self) -> Result<Chess, ConfigError> {
+        let mut chess = Chess::try_new(
+            self.initial_state.white_king,
+            self.initial_state.white_queen,
+            self.initial_state.black_king,
+            self.moves_limit,
+        )
+        .map_err(|_| ConfigError::ZeroMovesLimit)?;
+        chess
+            .set_strategy_by_name(&self.strategy)
+            .map_err(|_| ConfigError::UnknownStrategy)?;
+        chess.set_config(self.config);
+        Ok(chess)
+    }
+}
+
+/// Why a [`ProblemConfig`] could not be read.
+#[cfg(feature = "config-file")]
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file is not valid JSON or has unknown or mistyped fields.
+    Malformed(serde_json::Error),
+    /// The initial state could not be parsed.
+    InitialState(&'static str),
+    /// The strategy is not one of the built-in black king strategies.
+    UnknownStrategy,
+    /// The king moves flag differs from the way the interactor was built.
+    KingMovesMismatch,
+    /// The moves limit is zero, so the solution could not make a single move.
+    ZeroMovesLimit,
+}
+
+#[cfg(feature = "config-file")]
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(error) => write!(f, "malformed game config: {}", error),
+            Self::InitialState(error) => write!(f, "invalid initial state: {}", error),
+            Self::UnknownStrategy => f.write_str("unknown black king strategy"),
+            Self::KingMovesMismatch => {
+                f.write_str("king_moves does not match the king-moves-enabled build feature")
+            }
+            Self::ZeroMovesLimit => f.write_str("moves_limit must be positive"),
+        }
+    }
+}
+
+#[cfg(feature = "config-file")]
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Malformed(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            moves_limit_unit: MovesLimitUnit::default(),
+            log_fen: false,
+            require_check_announcement: false,
+            multiple_moves_per_line: false,
+            expect_handshake: false,
+            handshake_token: "ready".into(),
+            expect_echo_start: false,
+            labeled_initial_state: false,
+            forbid_allowing_stalemate: false,
+            lenient_diagnostics: false,
+            parse_retries: 0,
+            print_moves_remaining: false,
+            testlib_log: None,
+            prefer_opposition: false,
+            stalemate_counts_as_win: false,
+            record_transcript: false,
+            max_line_bytes: 4096,
+        }
+    }
+}
+
+/// A game between the solution playing white and the black king.
+///
+/// Copies of a game share the strategy, the policy and the history until one of them changes,
+/// and a game can be moved to another thread:
+///
+/// ```
+/// use std::str::FromStr;
+/// use chess_interactor::{Chess, ChessBoardPosition, GameOver};
+/// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+/// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+/// chess.set_strategy_by_name("greedy").unwrap();
+/// let outcome = std::thread::spawn(move || chess.play_with(&b"Qc1\nQc7\nQa7#\n"[..], Vec::new()))
+///     .join()
+///     .unwrap();
+/// assert!(matches!(outcome, GameOver::Checkmate));
+/// ```
+#[derive(Clone)]
+pub struct Chess {
+    white_king_position: ChessBoardPosition,
+    white_queen_position: ChessBoardPosition,
+    black_king_position: ChessBoardPosition,
+    moves: u64,
+    moves_limit: u64,
+    strategy: Arc<dyn BlackKingStrategy + Send + Sync>,
+    config: GameConfig,
+    renderer: Option<Arc<dyn BoardRenderer + Send + Sync>>,
+    policy: Arc<dyn MovePolicy + Send + Sync>,
+    /// How many times every position occurred with white to move, shared between the copies of
+    /// the game until one of them changes it
+    position_counts: Arc<HashMap<PositionKey, u32>>,
+    /// Set once a white move produced a `GameOver`, so no further moves are accepted
+    finished: bool,
+    /// Whether the black king had a single legal move the last time it moved
+    last_reply_forced: Option<bool>,
+    /// Solution moves since the last check
+    moves_since_progress: u64,
+    /// The plies played so far if [`GameConfig::record_transcript`] is set
+    transcript: Arc<Vec<TranscriptPly>>,
+    /// The last applied solution move with the claim its resulting position deserves
+    last_white_move: Option<Move>,
+}
+
+/// Distinguishes a malformed solution output from a well-formed but wrong one.
+///
+/// ```
+/// use std::str::FromStr;
+/// use chess_interactor::{Chess, ChessBoardPosition, GameOver, WrongInputKind};
+/// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+/// let kind = |white_king, white_queen, black_king, input: &[u8]| {
+///     let mut chess = Chess::new(pos(white_king), pos(white_queen), pos(black_king), 50);
+///     match chess.play_with(input, Vec::new()) {
+///         GameOver::WrongInput { kind, .. } => Some(kind),
+///         _ => None,
+///     }
+/// };
+/// assert_eq!(kind("a2", "h8", "a4", b"hello\n"), Some(WrongInputKind::Unparseable));
+/// assert_eq!(kind("a2", "h8", "a4", b"Qb3\n"), Some(WrongInputKind::IllegalMove));
+/// assert_eq!(kind("b6", "c1", "a8", b"Qc8\n"), Some(WrongInputKind::FalseClaim));
+/// assert_eq!(kind("b6", "c1", "a8", b"Qc8#\n"), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrongInputKind {
+    /// The line could not be read or parsed as a move.
+    Unparseable,
+    /// The move is well-formed but breaks the rules.
+    IllegalMove,
+    /// The checkmate announcement does not match the resulting position.
+    FalseClaim,
+}
+
+#[derive(Debug)]
+pub enum GameOver {
+    WrongInput {
+        kind: WrongInputKind,
+        error_message: String,
+        input: String,
+    },
+    /// The solution closed its output (e.g. it crashed) before the game was over.
+    SolverDisconnected,
+    TooManyMoves,
+    /// The white queen was left undefended next to the black king, so the black king captures
+    /// it and the remaining material cannot checkmate.
+    ///
+    /// The capture is resolved right after the white move, before any black king strategy is
+    /// consulted, so every defender takes a hanging queen.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("a1"), pos("d5"), pos("a7"), 50);
+    /// let mut output = Vec::new();
+    /// assert!(matches!(chess.play_with(&b"Qb7\n"[..], &mut output), GameOver::Draw));
+    /// assert_eq!(output, b"a1 d5 a7\n");
+    ///
+    /// // Claiming a checkmate with a hanging queen is a false claim rather than a draw
+    /// let mut chess = Chess::new(pos("a1"), pos("d5"), pos("a7"), 50);
+    /// assert!(matches!(
+    ///     chess.play_with(&b"Qb7#\n"[..], Vec::new()),
+    ///     GameOver::WrongInput { kind: WrongInputKind::FalseClaim, error_message, .. }
+    ///         if error_message == "no checkmate when claimed"
+    /// ));
+    /// ```
+    ///
+    /// The white king defends the queen from a diagonal neighbouring square too:
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameOver};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("c6"), pos("h7"), pos("a8"), 50);
+    /// assert!(matches!(chess.play_with(&b"Qb7#\n"[..], Vec::new()), GameOver::Checkmate));
+    ///
+    /// let mut chess = Chess::new(pos("c3"), pos("h4"), pos("a5"), 50);
+    /// assert_eq!(chess.apply_white_and_respond("Qb4".parse().unwrap()).unwrap(), pos("a6"));
+    ///
+    /// // One more square away the queen is hanging
+    /// let mut chess = Chess::new(pos("c2"), pos("h4"), pos("a5"), 50);
+    /// assert!(matches!(chess.apply_white_and_respond("Qb4".parse().unwrap()), Err(GameOver::Draw)));
+    /// ```
+    ///
+    /// With king moves enabled, a white king move that leaves the queen undefended next to the
+    /// black king is a draw too:
+    ///
+    /// ```
+    /// # #[cfg(feature = "king-moves-enabled")]
+    /// # {
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameOver};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("c3"), pos("b2"), pos("a1"), 50);
+    /// let mut output = Vec::new();
+    /// assert!(matches!(chess.play_with(&b"Kd4\n"[..], &mut output), GameOver::Draw));
+    /// assert_eq!(output, b"c3 b2 a1\n");
+    /// # }
+    /// ```
+    Draw,
+    /// The solution claimed a draw by threefold repetition and the claim was correct.
+    Repetition,
+    Stalemate,
+    Checkmate,
+    /// Two pieces ended up on the same square, which means a bug in the game engine or in a
+    /// custom [`MovePolicy`] or [`BlackKingStrategy`] rather than a wrong solution.
+    ///
+    /// Debug builds panic instead, so the inconsistency is noticed right where it happened.
+    ///
+    /// ```
+    /// use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// use std::str::FromStr;
+    /// use chess_interactor::{BlackKingStrategy, Chess, ChessBoardPosition, GameOver};
+    ///
+    /// /// Walks onto the queen even when it is defended
+    /// struct QueenEater;
+    ///
+    /// impl BlackKingStrategy for QueenEater {
+    ///     fn choose(&self, chess: &Chess, _: &[ChessBoardPosition]) -> ChessBoardPosition {
+    ///         chess.position_key().white_queen_position
+    ///     }
+    /// }
+    ///
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("e4"), pos("d1"), pos("e6"), 50);
+    /// chess.set_strategy(QueenEater);
+    /// let outcome = catch_unwind(AssertUnwindSafe(|| chess.play_with(&b"Qd5\n"[..], Vec::new())));
+    /// // Either panicked (the crate built in debug mode) or reported the overlap
+    /// if let Ok(outcome) = outcome {
+    ///     assert!(matches!(outcome, GameOver::PiecesOverlap { square } if square == pos("d5")));
+    /// }
+    /// ```
+    PiecesOverlap {
+        square: ChessBoardPosition,
+    },
+}
+
+impl GameOver {
+    /// Return the Testlib verdict token of the outcome: `_ok` for a checkmate, `_pe` for
+    /// a malformed or missing solution output, `_fail` for an internal error and `_wa` otherwise.
+    ///
+    /// ```
+    /// use chess_interactor::GameOver;
+    /// assert_eq!(GameOver::Checkmate.testlib_verdict(), "_ok");
+    /// assert_eq!(GameOver::SolverDisconnected.testlib_verdict(), "_pe");
+    /// assert_eq!(GameOver::Stalemate.testlib_verdict(), "_wa");
+    /// ```
+    pub fn testlib_verdict(&self) -> &'static str {
+        match self {
+            GameOver::Checkmate => "_ok",
+            GameOver::WrongInput {
+                kind: WrongInputKind::Unparseable,
+                ..
+            }
+            | GameOver::SolverDisconnected => "_pe",
+            GameOver::PiecesOverlap { .. } => "_fail",
+            _ => "_wa",
+        }
+    }
+
+    /// Return the side which won the game: white for a checkmate, black when the solution failed
+    /// (a wrong or missing move) and nobody for a draw or when the moves limit ran out.
+    ///
+    /// ```
+    /// use chess_interactor::{Color, GameOver, WrongInputKind};
+    /// assert_eq!(GameOver::Checkmate.winner(), Some(Color::White));
+    /// let wrong_input = GameOver::WrongInput {
+    ///     kind: WrongInputKind::IllegalMove,
+    ///     error_message: "queen tried to do impossible move".into(),
+    ///     input: "Qb3".into(),
+    /// };
+    /// assert_eq!(wrong_input.winner(), Some(Color::Black));
+    /// assert_eq!(GameOver::SolverDisconnected.winner(), Some(Color::Black));
+    /// assert_eq!(GameOver::TooManyMoves.winner(), None);
+    /// assert_eq!(GameOver::Draw.winner(), None);
+    /// assert_eq!(GameOver::Repetition.winner(), None);
+    /// assert_eq!(GameOver::Stalemate.winner(), None);
+    /// ```
+    ///
+    /// An internal error is nobody's win either:
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{ChessBoardPosition, GameOver};
+    /// let square = ChessBoardPosition::from_str("d5").unwrap();
+    /// assert_eq!(GameOver::PiecesOverlap { square }.winner(), None);
+    /// ```
+    pub fn winner(&self) -> Option<Color> {
+        match self {
+            GameOver::Checkmate => Some(Color::White),
+            GameOver::WrongInput { .. } | GameOver::SolverDisconnected => Some(Color::Black),
+            GameOver::TooManyMoves
+            | GameOver::Draw
+            | GameOver::Repetition
+            | GameOver::Stalemate
+            | GameOver::PiecesOverlap { .. } => None,
+        }
+    }
+}
+
+/// Render a one-line human-readable verdict of the game.
+///
+/// ```
+/// use chess_interactor::{summarize, GameOver, WrongInputKind};
+/// assert_eq!(summarize(&GameOver::Checkmate, 23), "Checkmate in 23 moves");
+/// assert_eq!(
+///     summarize(
+///         &GameOver::WrongInput {
+///             kind: WrongInputKind::IllegalMove,
+///             error_message: "queen tried to jump over white king".into(),
+///             input: "Qa8".into(),
+///         },
+///         11
+///     ),
+///     "Rejected at move 12: queen tried to jump over white king"
+/// );
+/// assert_eq!(summarize(&GameOver::TooManyMoves, 50), "No checkmate within 50 moves");
+/// assert_eq!(summarize(&GameOver::Checkmate, 1), "Checkmate in 1 move");
+/// assert_eq!(summarize(&GameOver::Stalemate, 1), "Stalemate after 1 move");
+/// ```
+pub fn summarize(outcome: &GameOver, moves: u64) -> String {
+    let moves_played = format!("{} {}", moves, if moves == 1 { "move" } else { "moves" });
+    match outcome {
+        GameOver::WrongInput { error_message, .. } => {
+            format!("Rejected at move {}: {}", moves + 1, error_message)
+        }
+        GameOver::SolverDisconnected => {
+            format!("Solution disconnected after {}", moves_played)
+        }
+        GameOver::TooManyMoves => format!("No checkmate within {}", moves_played),
+        GameOver::Draw => format!(
+            "Draw after {}: white queen was left undefended next to the black king",
+            moves_played
+        ),
+        GameOver::Repetition => format!("Draw by repetition claimed after {}", moves_played),
+        GameOver::Stalemate => format!("Stalemate after {}", moves_played),
+        GameOver::Checkmate => format!("Checkmate in {}", moves_played),
+        GameOver::PiecesOverlap { square } => {
+            format!(
+                "Internal error after {}: two pieces on {}",
+                moves_played, square
+            )
+        }
+    }
+}
+
+/// The outcome of a finished game together with the number of moves played.
+#[derive(Debug)]
+pub struct GameResult {
+    pub outcome: GameOver,
+    pub total_moves: u64,
+    /// Whether the solution checkmated the black king (or stalemated it, see
+    /// [`GameConfig::stalemate_counts_as_win`])
+    pub solver_succeeded: bool,
+    /// The final position rendered with the renderer set by [`Chess::set_renderer`], if any.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{BoardRenderer, Chess, ChessBoardPosition, TextRenderer};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    ///
+    /// struct FenRenderer;
+    /// impl BoardRenderer for FenRenderer {
+    ///     fn render(&self, chess: &Chess) -> Vec<u8> {
+    ///         chess.to_fen().into_bytes()
+    ///     }
+    /// }
+    ///
+    /// let mut chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    /// chess.set_renderer(FenRenderer);
+    /// let outcome = chess.play_with(&b"Qc8#\n"[..], Vec::new());
+    /// let result = chess.finish(outcome);
+    /// assert_eq!(result.final_position_render.unwrap(), b"k1Q5/8/1K6/8/8/8/8/8 w - - 2 2");
+    ///
+    /// let mut chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    /// let outcome = chess.play_with(&b"Qc8#\n"[..], Vec::new());
+    /// assert!(chess.clone().finish(outcome).final_position_render.is_none());
+    /// chess.set_renderer(TextRenderer);
+    /// let result = chess.clone().finish(chess_interactor::GameOver::Checkmate);
+    /// assert_eq!(result.final_position_render.unwrap(), chess.render_ascii().as_bytes());
+    /// ```
+    pub final_position_render: Option<Vec<u8>>,
+}
+
+impl GameResult {
+    /// Return the side which won the game like [`GameOver::winner`], with the solution winning
+    /// whenever it succeeded.
+    pub fn winner(&self) -> Option<Color> {
+        if self.solver_succeeded {
+            Some(Color::White)
+        } else {
+            self.outcome.winner()
+        }
+    }
+}
+
+impl std::fmt::Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&summarize(&self.outcome, self.total_moves))
+    }
+}
+
+/// One of the eight directions the queen moves in, north being towards the 8th rank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// Return the direction with the given (row, column) step.
+    ///
+    /// ```
+    /// use chess_interactor::Direction;
+    /// assert_eq!(Direction::from_delta((1, -1)), Some(Direction::NorthWest));
+    /// assert_eq!(Direction::from_delta((0, 0)), None);
+    /// assert_eq!(Direction::from_delta((2, 0)), None);
+    /// ```
+    pub fn from_delta(delta: (i8, i8)) -> Option<Direction> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|direction| direction.delta() == delta)
+    }
+
+    /// Return the (row, column) step of the direction.
+    ///
+    /// ```
+    /// use chess_interactor::Direction;
+    /// assert_eq!(Direction::North.delta(), (1, 0));
+    /// assert_eq!(Direction::SouthWest.delta(), (-1, -1));
+    /// ```
+    pub fn delta(self) -> (i8, i8) {
+        match self {
+            Self::North => (1, 0),
+            Self::NorthEast => (1, 1),
+            Self::East => (0, 1),
+            Self::SouthEast => (-1, 1),
+            Self::South => (-1, 0),
+            Self::SouthWest => (-1, -1),
+            Self::West => (0, -1),
+            Self::NorthWest => (1, -1),
+        }
+    }
+}
+
+/// Why the black king cannot step in one of the eight directions, see
+/// [`Chess::is_mate_explained`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EscapeSquareCover {
+    /// The square in this direction is off the board.
+    OffBoard(Direction),
+    /// The square is attacked by the listed white pieces.
+    Attacked {
+        square: ChessBoardPosition,
+        by: Vec<ChessPiece>,
+    },
+}
+
+/// The reasons a checkmate is a checkmate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MateExplanation {
+    pub checking_piece: ChessPiece,
+    /// One entry per direction in the [`Direction::ALL`] order.
+    pub escape_squares: Vec<EscapeSquareCover>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    White,
+    Black,
+}
+
+/// Check that the position could occur in a game: the pieces occupy distinct squares, the kings
+/// are not next to each other and the side which is not to move is not in check.
+///
+/// ```
+/// use std::str::FromStr;
+/// use chess_interactor::{is_legal_position, ChessBoardPosition, Color};
+/// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+/// assert_eq!(is_legal_position(pos("a2"), pos("h8"), pos("a4"), Color::White), Ok(()));
+/// assert_eq!(
+///     is_legal_position(pos("a2"), pos("a2"), pos("a4"), Color::White),
+///     Err("pieces share a square")
+/// );
+/// assert_eq!(
+///     is_legal_position(pos("a2"), pos("h8"), pos("b3"), Color::Black),
+///     Err("kings are next to each other")
+/// );
+/// // The black king may be in check only when it is its turn to move
+/// assert_eq!(is_legal_position(pos("a2"), pos("h4"), pos("a4"), Color::Black), Ok(()));
+/// assert_eq!(
+///     is_legal_position(pos("a2"), pos("h4"), pos("a4"), Color::White),
+///     Err("black king is in check with white to move")
+/// );
+/// // The white king shields the black king from the queen
+/// assert_eq!(is_legal_position(pos("d4"), pos("h4"), pos("a4"), Color::White), Ok(()));
+/// ```
+pub fn is_legal_position(
+    white_king_position: ChessBoardPosition,
+    white_queen_position: ChessBoardPosition,
+    black_king_position: ChessBoardPosition,
+    side_to_move: Color,
+) -> Result<(), &'static str> {
+    if white_king_position == white_queen_position
+        || white_king_position == black_king_position
+        || white_queen_position == black_king_position
+    {
+        return Err("pieces share a square");
+    }
+    if let Ok((1, _)) = white_king_position.queen_distance(&black_king_position) {
+        return Err("kings are next to each other");
+    }
+    // The lone black king can never give check, so only the black king can be in check
+    if side_to_move == Color::White
+        && Chess::new(
+            white_king_position,
+            white_queen_position,
+            black_king_position,
+            0,
+        )
+        .queen_checks_black_king()
+    {
+        return Err("black king is in check with white to move");
+    }
+    Ok(())
+}
+
+/// Return the piece placement field of a FEN, from rank 8 down to rank 1.
+fn fen_placement(piece_at: impl Fn(ChessBoardPosition) -> Option<char>) -> String {
+    let mut placement = String::new();
+    for row in (0..BOARD_SIZE).rev() {
+        let mut empty_squares = 0;
+        for column in 0..BOARD_SIZE {
+            let piece = match piece_at(ChessBoardPosition { row, column }) {
+                Some(piece) => piece,
+                None => {
+                    empty_squares += 1;
+                    continue;
+                }
+            };
+            if empty_squares > 0 {
+                placement.push_str(&empty_squares.to_string());
+                empty_squares = 0;
+            }
+            placement.push(piece);
+        }
+        if empty_squares > 0 {
+            placement.push_str(&empty_squares.to_string());
+        }
+        if row > 0 {
+            placement.push('/');
+        }
+    }
+    placement
+}
+
+/// Move every piece of a FEN produced by [`Chess::to_fen`] to the mirrored square, keeping the
+/// other fields.
+fn mirror_fen(fen: &str, mirror: &impl Fn(ChessBoardPosition) -> ChessBoardPosition) -> String {
+    let (placement, other_fields) = fen.split_once(' ').expect("FEN has several fields");
+    let mut board = [[None; BOARD_SIZE as usize]; BOARD_SIZE as usize];
+    for (rank, row) in placement.split('/').zip((0..BOARD_SIZE).rev()) {
+        let mut column = 0;
+        for symbol in rank.chars() {
+            match symbol.to_digit(10) {
+                Some(empty_squares) => column += empty_squares as u8,
+                None => {
+                    let to = mirror(ChessBoardPosition { row, column });
+                    board[usize::from(to.row)][usize::from(to.column)] = Some(symbol);
+                    column += 1;
+                }
+            }
+        }
+    }
+    let placement =
+        fen_placement(|position| board[usize::from(position.row)][usize::from(position.column)]);
+    format!("{} {}", placement, other_fields)
+}
+
+/// Return the inputs of the parsed moves of a batch.
+fn batch_inputs<'a>(chess_moves: &[(&'a str, Move)]) -> Vec<&'a str> {
+    chess_moves
+        .iter()
+        .map(|&(move_input, _)| move_input)
+        .collect()
+}
+
+/// Read the next solution line into the reused `bytes` buffer and return it.
+fn read_solution_line<'a, R: BufRead>(
+    input: &mut R,
+    bytes: &'a mut Vec<u8>,
+    max_line_bytes: usize,
+) -> Result<&'a str, GameOver> {
+    bytes.clear();
+    // One more byte than allowed is enough to tell an oversized line from the line break
+    let limit = (max_line_bytes as u64).saturating_add(1);
+    match Read::take(&mut *input, limit).read_until(b'\n', bytes) {
+        Ok(0) => return Err(GameOver::SolverDisconnected),
+        Ok(_) => (),
+        Err(error) => {
+            return Err(GameOver::WrongInput {
+                kind: WrongInputKind::Unparseable,
+                error_message: format!("Reading a new line from a solution failed: {:?}", error),
+                input: "".into(),
+            });
+        }
+    }
+    if bytes.len() > max_line_bytes && bytes.last() != Some(&b'\n') {
+        return Err(GameOver::WrongInput {
+            kind: WrongInputKind::Unparseable,
+            error_message: "input line too long".into(),
+            input: format!(
+                "{}...",
+                String::from_utf8_lossy(&bytes[..bytes.len().min(16)])
+            ),
+        });
+    }
+    if !bytes.is_ascii() {
+        return Err(GameOver::WrongInput {
+            kind: WrongInputKind::Unparseable,
+            error_message: "non-ASCII input".into(),
+            input: String::from_utf8_lossy(bytes).trim().into(),
+        });
+    }
+    Ok(std::str::from_utf8(bytes).expect("ASCII is valid UTF-8"))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ChessBoardCell {
+    Available,
+    King,
+    Attackable,
+}
+
+type AttackMap = [[ChessBoardCell; BOARD_SIZE as usize]; BOARD_SIZE as usize];
+
+const SQUARES_COUNT: usize = BOARD_SIZE as usize * BOARD_SIZE as usize;
+
+/// SplitMix64 output for the given index, used to fill the Zobrist keys table at compile time.
+const fn splitmix64(index: u64) -> u64 {
+    let mut z = index.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Zobrist keys of the white king, the white queen and the black king on every square.
+const ZOBRIST_PIECE_SQUARE_KEYS: [[u64; SQUARES_COUNT]; 3] = {
+    let mut keys = [[0; SQUARES_COUNT]; 3];
+    let mut index = 0;
+    while index < 3 * SQUARES_COUNT {
+        keys[index / SQUARES_COUNT][index % SQUARES_COUNT] = splitmix64(index as u64 + 1);
+        index += 1;
+    }
+    keys
+};
+
+/// Zobrist key of white to move, the side to move whenever the solution is asked for a move.
+const ZOBRIST_WHITE_TO_MOVE_KEY: u64 = splitmix64(3 * SQUARES_COUNT as u64 + 1);
+
+/// Render the attack map as a text grid from rank 8 down to rank 1, marking the white king
+/// with `K`, attacked squares with `x` and available squares with `.`.
+fn render_attack_map(board: &AttackMap) -> String {
+    let mut grid = String::new();
+    for (row, board_row) in board.iter().enumerate().rev() {
+        grid.push_str(&format!("{} ", row + 1));
+        for cell in board_row {
+            grid.push(match cell {
+                ChessBoardCell::Available => '.',
+                ChessBoardCell::King => 'K',
+                ChessBoardCell::Attackable => 'x',
+            });
+        }
+        grid.push('\n');
+    }
+    grid.push_str("  abcdefgh");
+    grid
+}
+
+impl Chess {
+    pub fn new(
+        white_king_position: ChessBoardPosition,
+        white_queen_position: ChessBoardPosition,
+        black_kind_position: ChessBoardPosition,
+        moves_limit: u64,
+    ) -> Self {
+        Self {
+            white_king_position,
+            white_queen_position,
+            black_king_position: black_kind_position,
+            moves: 0,
+            moves_limit,
+            strategy: Arc::new(NaiveStrategy),
+            config: GameConfig::default(),
+            renderer: None,
+            policy: Arc::new(StandardPolicy),
+            position_counts: Arc::new(
+                std::iter::once((
+                    PositionKey {
+                        white_king_position,
+                        white_queen_position,
+                        black_king_position: black_kind_position,
+                    },
+                    1,
+                ))
+                .collect(),
+            ),
+            finished: false,
+            last_reply_forced: None,
+            moves_since_progress: 0,
+            transcript: Arc::new(Vec::new()),
+            last_white_move: None,
+        }
+    }
+
+    /// Like [`Chess::new`], but reject a zero moves limit, with which the game would be over
+    /// before the solution could make a single move.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameOver};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert!(Chess::try_new(pos("b6"), pos("c1"), pos("a8"), 1).is_ok());
+    /// assert_eq!(
+    ///     Chess::try_new(pos("b6"), pos("c1"), pos("a8"), 0).err(),
+    ///     Some("moves limit must be positive")
+    /// );
+    ///
+    /// // `Chess::new` accepts the zero limit, and the game is over right away
+    /// let mut chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 0);
+    /// assert!(matches!(chess.play_with(&b"Qc8#\n"[..], Vec::new()), GameOver::TooManyMoves));
+    /// ```
+    pub fn try_new(
+        white_king_position: ChessBoardPosition,
+        white_queen_position: ChessBoardPosition,
+        black_king_position: ChessBoardPosition,
+        moves_limit: u64,
+    ) -> Result<Self, &'static str> {
+        if moves_limit == 0 {
+            return Err("moves limit must be positive");
+        }
+        Ok(Self::new(
+            white_king_position,
+            white_queen_position,
+            black_king_position,
+            moves_limit,
+        ))
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: GameConfig) {
+        self.config = config;
+    }
+
+    pub fn set_strategy<S: BlackKingStrategy + Send + Sync + 'static>(&mut self, strategy: S) {
+        self.strategy = Arc::new(strategy);
+    }
+
+    /// Replace the rules checking the white moves, [`StandardPolicy`] by default.
+    pub fn set_policy<P: MovePolicy + Send + Sync + 'static>(&mut self, policy: P) {
+        self.policy = Arc::new(policy);
+    }
+
+    /// Render the final position with the given renderer when the game is finished, see
+    /// [`GameResult::final_position_render`].
+    pub fn set_renderer<R: BoardRenderer + Send + Sync + 'static>(&mut self, renderer: R) {
+        self.renderer = Some(Arc::new(renderer));
+    }
+
+    /// Select one of the built-in black king strategies: `naive` (default) or `greedy`.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// assert_eq!(chess.set_strategy_by_name("greedy"), Ok(()));
+    /// assert_eq!(chess.set_strategy_by_name("naive"), Ok(()));
+    /// assert_eq!(chess.set_strategy_by_name("optimal"), Err("unknown black king strategy"));
+    /// ```
+    pub fn set_strategy_by_name(&mut self, name: &str) -> Result<(), &'static str> {
+        match name {
+            "naive" => self.set_strategy(NaiveStrategy),
+            "greedy" => self.set_strategy(GreedyStrategy),
+            _ => return Err("unknown black king strategy"),
+        }
+        Ok(())
+    }
+
+    pub fn moves(&self) -> u64 {
+        self.moves
+    }
+
+    /// Return how many more moves the solution may make before the moves limit is reached.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig, MovesLimitUnit};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 3);
+    /// assert_eq!(chess.moves_remaining(), 3);
+    /// chess.apply_white_and_respond("Qb1".parse().unwrap()).unwrap();
+    /// assert_eq!(chess.moves_remaining(), 2);
+    /// chess.apply_white_and_respond("Qa2".parse().unwrap()).unwrap();
+    /// chess.apply_white_and_respond("Qb1".parse().unwrap()).unwrap();
+    /// assert_eq!(chess.moves_remaining(), 0);
+    ///
+    /// // Every solution move takes two plies, the last one answered or not
+    /// let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 3);
+    /// chess.set_config(GameConfig { moves_limit_unit: MovesLimitUnit::Plies, ..GameConfig::default() });
+    /// assert_eq!(chess.moves_remaining(), 2);
+    /// ```
+    pub fn moves_remaining(&self) -> u64 {
+        let moves_limit = match self.config.moves_limit_unit {
+            MovesLimitUnit::Moves => self.moves_limit,
+            // Every solution move so far has been answered by the black king
+            MovesLimitUnit::Plies => self.moves_limit / 2 + self.moves_limit % 2,
+        };
+        moves_limit.saturating_sub(self.moves)
+    }
+
+    /// Render the board from rank 8 down to rank 1 using `K`, `Q` and `k` for the white king,
+    /// the white queen and the black king respectively.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// assert_eq!(
+    ///     chess.render_ascii(),
+    ///     "8 . . . . . . . Q\n\
+    ///      7 . . . . . . . .\n\
+    ///      6 . . . . . . . .\n\
+    ///      5 . . . . . . . .\n\
+    ///      4 k . . . . . . .\n\
+    ///      3 . . . . . . . .\n\
+    ///      2 K . . . . . . .\n\
+    ///      1 . . . . . . . .\n\
+    ///      \x20 a b c d e f g h\n"
+    /// );
+    /// ```
+    pub fn render_ascii(&self) -> String {
+        self.render_ascii_from(Color::White)
+    }
+
+    /// Render the board like [`Chess::render_ascii`] as seen by the given side: from black's
+    /// side rank 1 is at the top and the h-file is on the left.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, Color};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// assert_eq!(chess.render_ascii_from(Color::White), chess.render_ascii());
+    /// assert_eq!(
+    ///     chess.render_ascii_from(Color::Black),
+    ///     "1 . . . . . . . .\n\
+    ///      2 . . . . . . . K\n\
+    ///      3 . . . . . . . .\n\
+    ///      4 . . . . . . . k\n\
+    ///      5 . . . . . . . .\n\
+    ///      6 . . . . . . . .\n\
+    ///      7 . . . . . . . .\n\
+    ///      8 Q . . . . . . .\n\
+    ///      \x20 h g f e d c b a\n"
+    /// );
+    /// ```
+    pub fn render_ascii_from(&self, side: Color) -> String {
+        self.render_with(['K', 'Q', 'k', '.'], side)
+    }
+
+    /// Render the board like [`Chess::render_ascii`] but with Unicode chess glyphs. Terminals
+    /// which render the glyphs double-width should use [`Chess::render_ascii`] instead.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// let board = chess.render_unicode();
+    /// let lines: Vec<&str> = board.lines().collect();
+    /// assert_eq!(lines[0], "8 · · · · · · · ♕");
+    /// assert_eq!(lines[4], "4 ♚ · · · · · · ·");
+    /// assert_eq!(lines[6], "2 ♔ · · · · · · ·");
+    /// assert_eq!(lines[8], "  a b c d e f g h");
+    /// ```
+    pub fn render_unicode(&self) -> String {
+        self.render_unicode_from(Color::White)
+    }
+
+    /// Render the board like [`Chess::render_unicode`] as seen by the given side, see
+    /// [`Chess::render_ascii_from`].
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, Color};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// let board = chess.render_unicode_from(Color::Black);
+    /// let lines: Vec<&str> = board.lines().collect();
+    /// assert_eq!(lines[1], "2 · · · · · · · ♔");
+    /// assert_eq!(lines[3], "4 · · · · · · · ♚");
+    /// assert_eq!(lines[7], "8 ♕ · · · · · · ·");
+    /// assert_eq!(lines[8], "  h g f e d c b a");
+    /// ```
+    pub fn render_unicode_from(&self, side: Color) -> String {
+        self.render_with(['♔', '♕', '♚', '·'], side)
+    }
+
+    fn render_with(
+        &self,
+        [white_king, white_queen, black_king, empty]: [char; 4],
+        side: Color,
+    ) -> String {
+        let flip = |index: u8| match side {
+            Color::White => index,
+            Color::Black => BOARD_LAST_INDEX - index,
+        };
+        let mut board = String::new();
+        for row in (0..BOARD_SIZE).rev().map(flip) {
+            board.push(char::from(b'1' + row));
+            for column in (0..BOARD_SIZE).map(flip) {
+                let position = ChessBoardPosition { row, column };
+                board.push(' ');
+                board.push(if position == self.white_king_position {
+                    white_king
+                } else if position == self.white_queen_position {
+                    white_queen
+                } else if position == self.black_king_position {
+                    black_king
+                } else {
+                    empty
+                });
+            }
+            board.push('\n');
+        }
+        board.push(' ');
+        for column in (0..BOARD_SIZE).map(flip) {
+            board.push(' ');
+            board.push(char::from(b'a' + column));
+        }
+        board.push('\n');
+        board
+    }
+
+    /// Return the same game with the board flipped left to right (files a and h swapped).
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("f7"), pos("a1"), pos("h8"), 50);
+    /// let mirrored = chess.mirror_horizontal();
+    /// assert!(mirrored.same_position(&Chess::new(pos("c7"), pos("h1"), pos("a8"), 50)));
+    /// assert_eq!(chess.black_reply_candidates(), [(pos("h8"), pos("h7"))]);
+    /// assert_eq!(mirrored.black_reply_candidates(), [(pos("a8"), pos("a7"))]);
+    /// ```
+    pub fn mirror_horizontal(&self) -> Chess {
+        self.mirror_with(|position| ChessBoardPosition {
+            row: position.row,
+            column: BOARD_LAST_INDEX - position.column,
+        })
+    }
+
+    /// Return the same game with the board flipped top to bottom (ranks 1 and 8 swapped).
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("f7"), pos("a1"), pos("h8"), 50);
+    /// let mirrored = chess.mirror_vertical();
+    /// assert!(mirrored.same_position(&Chess::new(pos("f2"), pos("a8"), pos("h1"), 50)));
+    /// assert_eq!(mirrored.black_reply_candidates(), [(pos("h1"), pos("h2"))]);
+    /// ```
+    ///
+    /// The recorded transcript is mirrored with the board:
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// chess.set_config(GameConfig { record_transcript: true, ..GameConfig::default() });
+    /// chess.apply_white_and_respond("Qc1".parse().unwrap()).unwrap();
+    /// let mirrored = chess.mirror_vertical();
+    /// let transcript = mirrored.transcript();
+    /// assert_eq!(transcript[0].white_move.to_string(), "Qc8");
+    /// assert_eq!(transcript[0].black_reply, Some(pos("b1")));
+    /// assert_eq!(transcript[0].resulting_fen.as_deref(), Some("2Q5/8/8/8/8/1K6/8/1k6 w - - 2 2"));
+    /// ```
+    pub fn mirror_vertical(&self) -> Chess {
+        self.mirror_with(|position| ChessBoardPosition {
+            row: BOARD_LAST_INDEX - position.row,
+            column: position.column,
+        })
+    }
+
+    fn mirror_with(&self, mirror: impl Fn(ChessBoardPosition) -> ChessBoardPosition) -> Chess {
+        let position_counts = self
+            .position_counts
+            .iter()
+            .map(|(position_key, &count)| {
+                (
+                    PositionKey {
+                        white_king_position: mirror(position_key.white_king_position),
+                        white_queen_position: mirror(position_key.white_queen_position),
+                        black_king_position: mirror(position_key.black_king_position),
+                    },
+                    count,
+                )
+            })
+            .collect();
+        let transcript = self
+            .transcript
+            .iter()
+            .map(|ply| TranscriptPly {
+                white_move: Move {
+                    to: mirror(ply.white_move.to),
+                    ..ply.white_move
+                },
+                black_reply: ply.black_reply.map(&mirror),
+                resulting_fen: ply
+                    .resulting_fen
+                    .as_ref()
+                    .map(|fen| mirror_fen(fen, &mirror)),
+            })
+            .collect();
+        Chess {
+            white_king_position: mirror(self.white_king_position),
+            white_queen_position: mirror(self.white_queen_position),
+            black_king_position: mirror(self.black_king_position),
+            position_counts: Arc::new(position_counts),
+            transcript: Arc::new(transcript),
+            last_white_move: self.last_white_move.map(|chess_move| Move {
+                to: mirror(chess_move.to),
+                ..chess_move
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// Return the FEN of the position with white (the solution) to move.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// assert_eq!(chess.to_fen(), "7Q/8/8/8/k7/8/K7/8 w - - 0 1");
+    /// let chess = Chess::new(pos("e1"), pos("d1"), pos("e8"), 50);
+    /// assert_eq!(chess.to_fen(), "4k3/8/8/8/8/8/8/3QK3 w - - 0 1");
+    /// ```
+    pub fn to_fen(&self) -> String {
+        self.fen(false)
+    }
+
+    /// Create a game from the FEN of a position with white to move. The castling, en passant
+    /// and move counter fields are ignored.
+    ///
+    /// ```
+    /// use chess_interactor::{Chess, FenError};
+    /// let chess = Chess::from_fen("7Q/8/8/8/k7/8/K7/8 w - - 0 1", 50).unwrap();
+    /// assert_eq!(chess.initial_state().to_string(), "a2 h8 a4");
+    /// assert_eq!(chess.to_fen(), "7Q/8/8/8/k7/8/K7/8 w - - 0 1");
+    /// assert!(Chess::from_fen("7Q/8/8/8/k7/8/K7/8 w", 50).is_ok());
+    ///
+    /// assert_eq!(Chess::from_fen("7Q/8/8/8/k7/8/K7/8", 50).err(), Some(FenError::TooFewFields));
+    /// assert_eq!(Chess::from_fen("7Q/8/8/8/k7/8/K7/8 b", 50).err(), Some(FenError::BadSideToMove));
+    /// assert_eq!(
+    ///     Chess::from_fen("7Q/8/8/8/k7/8/K7/7R w - - 0 1", 50).err(),
+    ///     Some(FenError::WrongPieceSet)
+    /// );
+    /// assert_eq!(
+    ///     Chess::from_fen("7Q/8/8/8/k7/8/K7/Q7 w - - 0 1", 50).err(),
+    ///     Some(FenError::WrongPieceSet)
+    /// );
+    /// assert_eq!(
+    ///     Chess::from_fen("7Q/8/8/8/k8/8/K7/8 w - - 0 1", 50).err(),
+    ///     Some(FenError::BadPiecePlacement)
+    /// );
+    /// assert_eq!(
+    ///     Chess::from_fen("7Q/8/8/k7/8/K7/8 w - - 0 1", 50).err(),
+    ///     Some(FenError::BadPiecePlacement)
+    /// );
+    /// ```
+    pub fn from_fen(fen: &str, moves_limit: u64) -> Result<Self, FenError> {
+        let mut fields = fen.split_whitespace();
+        let (placement, side_to_move) = match (fields.next(), fields.next()) {
+            (Some(placement), Some(side_to_move)) => (placement, side_to_move),
+            _ => return Err(FenError::TooFewFields),
+        };
+
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != usize::from(BOARD_SIZE) {
+            return Err(FenError::BadPiecePlacement);
+        }
+        let mut white_king_position = None;
+        let mut white_queen_position = None;
+        let mut black_king_position = None;
+        for (rank, row) in ranks.iter().zip((0..BOARD_SIZE).rev()) {
+            let mut column = 0;
+            for symbol in rank.chars() {
+                if column >= BOARD_SIZE {
+                    return Err(FenError::BadPiecePlacement);
+                }
+                if let Some(empty_squares) = symbol.to_digit(10) {
+                    if empty_squares == 0 || empty_squares > u32::from(BOARD_SIZE - column) {
+                        return Err(FenError::BadPiecePlacement);
+                    }
+                    column += empty_squares as u8;
+                    continue;
+                }
+                let piece_position = match symbol {
+                    'K' => &mut white_king_position,
+                    'Q' => &mut white_queen_position,
+                    'k' => &mut black_king_position,
+                    'P' | 'N' | 'B' | 'R' | 'p' | 'n' | 'b' | 'r' | 'q' => {
+                        return Err(FenError::WrongPieceSet)
+                    }
+                    _ => return Err(FenError::BadPiecePlacement),
+                };
+                if piece_position.is_some() {
+                    return Err(FenError::WrongPieceSet);
+                }
+                *piece_position = Some(ChessBoardPosition { row, column });
+                column += 1;
+            }
+            if column != BOARD_SIZE {
+                return Err(FenError::BadPiecePlacement);
+            }
+        }
+
+        if side_to_move != "w" {
+            return Err(FenError::BadSideToMove);
+        }
+        match (
+            white_king_position,
+            white_queen_position,
+            black_king_position,
+        ) {
+            (Some(white_king_position), Some(white_queen_position), Some(black_king_position)) => {
+                Ok(Self::new(
+                    white_king_position,
+                    white_queen_position,
+                    black_king_position,
+                    moves_limit,
+                ))
+            }
+            _ => Err(FenError::WrongPieceSet),
+        }
+    }
+
+    /// Return the board as 64 characters from a8 to h8 down to a1 to h1, using `K`, `Q`, `k`
+    /// and `.` for the white king, the white queen, the black king and an empty square.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// let board = chess.to_board_string();
+    /// assert_eq!(board.len(), 64);
+    /// assert_eq!(&board[..8], ".......Q");
+    /// assert_eq!(&board[32..40], "k.......");
+    /// assert_eq!(&board[48..56], "K.......");
+    /// let parsed = Chess::from_board_string(&board, 50).unwrap();
+    /// assert!(parsed.same_position(&chess));
+    /// assert_eq!(parsed.to_board_string(), board);
+    /// ```
+    pub fn to_board_string(&self) -> String {
+        let mut board = String::with_capacity(usize::from(BOARD_SIZE * BOARD_SIZE));
+        for row in (0..BOARD_SIZE).rev() {
+            for column in 0..BOARD_SIZE {
+                let position = ChessBoardPosition { row, column };
+                board.push(if position == self.white_king_position {
+                    'K'
+                } else if position == self.white_queen_position {
+                    'Q'
+                } else if position == self.black_king_position {
+                    'k'
+                } else {
+                    '.'
+                });
+            }
+        }
+        board
+    }
+
+    /// Parse the 64 characters board of [`Chess::to_board_string`], which must hold exactly one
+    /// white king, one white queen and one black king.
+    ///
+    /// ```
+    /// use chess_interactor::Chess;
+    /// let board = |placement: &str| format!("{:.<64}", placement);
+    /// assert!(Chess::from_board_string(&board("KQk"), 50).is_ok());
+    /// assert_eq!(
+    ///     Chess::from_board_string("KQk", 50).err(),
+    ///     Some("board string must be 64 characters long")
+    /// );
+    /// assert_eq!(Chess::from_board_string(&board("KQkq"), 50).err(), Some("unknown board symbol"));
+    /// assert_eq!(Chess::from_board_string(&board("KQ"), 50).err(), Some("wrong piece set"));
+    /// assert_eq!(Chess::from_board_string(&board("KQkQ"), 50).err(), Some("wrong piece set"));
+    /// ```
+    pub fn from_board_string(board: &str, moves_limit: u64) -> Result<Self, &'static str> {
+        if board.len() != usize::from(BOARD_SIZE * BOARD_SIZE) {
+            return Err("board string must be 64 characters long");
+        }
+        let mut white_king_position = None;
+        let mut white_queen_position = None;
+        let mut black_king_position = None;
+        for (index, symbol) in board.chars().enumerate() {
+            let piece_position = match symbol {
+                'K' => &mut white_king_position,
+                'Q' => &mut white_queen_position,
+                'k' => &mut black_king_position,
+                '.' => continue,
+                _ => return Err("unknown board symbol"),
+            };
+            if piece_position.is_some() {
+                return Err("wrong piece set");
+            }
+            let index = index as u8;
+            *piece_position = Some(ChessBoardPosition {
+                row: BOARD_LAST_INDEX - index / BOARD_SIZE,
+                column: index % BOARD_SIZE,
+            });
+        }
+        match (
+            white_king_position,
+            white_queen_position,
+            black_king_position,
+        ) {
+            (Some(white_king_position), Some(white_queen_position), Some(black_king_position)) => {
+                Ok(Self::new(
+                    white_king_position,
+                    white_queen_position,
+                    black_king_position,
+                    moves_limit,
+                ))
+            }
+            _ => Err("wrong piece set"),
+        }
+    }
+
+    fn fen(&self, black_to_move: bool) -> String {
+        let mut fen = fen_placement(|position| {
+            if position == self.white_king_position {
+                Some('K')
+            } else if position == self.white_queen_position {
+                Some('Q')
+            } else if position == self.black_king_position {
+                Some('k')
+            } else {
+                None
+            }
+        });
+        // There are no captures or pawn moves, so the halfmove clock counts all the plies
+        let (side_to_move, halfmoves, fullmoves) = if black_to_move {
+            ('b', self.moves * 2 - 1, self.moves)
+        } else {
+            ('w', self.moves * 2, self.moves + 1)
+        };
+        fen.push_str(&format!(
+            " {} - - {} {}",
+            side_to_move, halfmoves, fullmoves
+        ));
+        fen
+    }
+
+    pub fn initial_state(&self) -> InitialState {
+        InitialState {
+            white_king: self.white_king_position,
+            white_queen: self.white_queen_position,
+            black_king: self.black_king_position,
+        }
+    }
+
+    pub fn position_key(&self) -> PositionKey {
+        PositionKey {
+            white_king_position: self.white_king_position,
+            white_queen_position: self.white_queen_position,
+            black_king_position: self.black_king_position,
+        }
+    }
+
+    /// Return the same key for all the positions the eight board symmetries (rotations and
+    /// reflections) map onto each other. Each symmetric variant is packed as
+    /// `white_king << 12 | white_queen << 6 | black_king` with squares numbered `row * 8 + column`
+    /// from a1, and the smallest one is returned.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// let key = chess.canonical_key();
+    /// assert_eq!(chess.mirror_horizontal().canonical_key(), key);
+    /// assert_eq!(chess.mirror_vertical().canonical_key(), key);
+    /// // The same position rotated by 90 degrees
+    /// assert_eq!(Chess::new(pos("f7"), pos("b6"), pos("h8"), 50).canonical_key(), key);
+    /// assert_ne!(Chess::new(pos("b6"), pos("c3"), pos("a8"), 50).canonical_key(), key);
+    /// // The king in the a1 corner with the black king along the first rank
+    /// assert_eq!(Chess::new(pos("a1"), pos("h8"), pos("c1"), 50).canonical_key(), 0 << 12 | 63 << 6 | 2);
+    /// ```
+    pub fn canonical_key(&self) -> u32 {
+        let transform = |position: ChessBoardPosition, symmetry: u8| {
+            let (mut row, mut column) = (position.row, position.column);
+            if symmetry & 1 != 0 {
+                column = BOARD_LAST_INDEX - column;
+            }
+            if symmetry & 2 != 0 {
+                row = BOARD_LAST_INDEX - row;
+            }
+            if symmetry & 4 != 0 {
+                std::mem::swap(&mut row, &mut column);
+            }
+            u32::from(row) * u32::from(BOARD_SIZE) + u32::from(column)
+        };
+        (0..8)
+            .map(|symmetry| {
+                transform(self.white_king_position, symmetry) << 12
+                    | transform(self.white_queen_position, symmetry) << 6
+                    | transform(self.black_king_position, symmetry)
+            })
+            .min()
+            .expect("there are eight symmetries")
+    }
+
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// assert!(chess.same_position(&Chess::new(pos("a2"), pos("h8"), pos("a4"), 10)));
+    /// assert!(!chess.same_position(&Chess::new(pos("a2"), pos("h7"), pos("a4"), 50)));
+    /// ```
+    pub fn same_position(&self, other: &Chess) -> bool {
+        self.position_key() == other.position_key()
+    }
+
+    /// Return the Zobrist hash of the position.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 50);
+    /// let zobrist = chess.zobrist();
+    /// chess.apply_white_and_respond("Qb1".parse().unwrap()).unwrap();
+    /// chess.apply_white_and_respond("Qa2".parse().unwrap()).unwrap();
+    /// let other = Chess::new(pos("a1"), pos("a2"), chess.initial_state().black_king, 10);
+    /// assert_eq!(chess.zobrist(), other.zobrist());
+    ///
+    /// let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 50);
+    /// chess.apply_white_and_respond("Qb1".parse().unwrap()).unwrap();
+    /// assert_ne!(chess.zobrist(), zobrist);
+    /// assert_ne!(Chess::new(pos("a1"), pos("b1"), pos("h8"), 50).zobrist(), zobrist);
+    /// ```
+    pub fn zobrist(&self) -> u64 {
+        let square = |position: ChessBoardPosition| {
+            usize::from(position.row) * usize::from(BOARD_SIZE) + usize::from(position.column)
+        };
+        ZOBRIST_PIECE_SQUARE_KEYS[0][square(self.white_king_position)]
+            ^ ZOBRIST_PIECE_SQUARE_KEYS[1][square(self.white_queen_position)]
+            ^ ZOBRIST_PIECE_SQUARE_KEYS[2][square(self.black_king_position)]
+            ^ ZOBRIST_WHITE_TO_MOVE_KEY
+    }
+
+    /// Return how many times the current position occurred in the game with white to move,
+    /// including the current occurrence.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 50);
+    /// assert_eq!(chess.position_repetitions(), 1);
+    /// chess.apply_white_and_respond("Qb1".parse().unwrap()).unwrap();
+    /// chess.apply_white_and_respond("Qa2".parse().unwrap()).unwrap();
+    /// assert_eq!(chess.position_repetitions(), 2);
+    /// ```
+    pub fn position_repetitions(&self) -> u32 {
+        self.position_counts
+            .get(&self.position_key())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Return how many distinct positions with white to move occurred in the game, including the
+    /// starting one.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// chess.play_with(&b"Qc1\nQc7\nQa7#\n"[..], Vec::new());
+    /// assert_eq!(chess.unique_positions_visited(), 3);
+    ///
+    /// // Shuffling the queen back and forth does not visit anything new
+    /// let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 50);
+    /// chess.play_with(&b"Qb1\nQa2\nQb1\nQa2\nQb1\nQa2\n"[..], Vec::new());
+    /// assert_eq!(chess.moves(), 6);
+    /// assert_eq!(chess.unique_positions_visited(), 2);
+    /// ```
+    pub fn unique_positions_visited(&self) -> usize {
+        self.position_counts.len()
+    }
+
+    /// Return the number of solution moves since the last one giving a check, or since the start
+    /// of the game if there was no check yet.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 50);
+    /// assert_eq!(chess.moves_since_progress(), 0);
+    /// assert_eq!(chess.apply_white_and_respond("Qb1".parse().unwrap()).unwrap(), pos("g8"));
+    /// assert_eq!(chess.moves_since_progress(), 1);
+    /// // Checks along the a2-g8 diagonal
+    /// chess.apply_white_and_respond("Qa2".parse().unwrap()).unwrap();
+    /// assert_eq!(chess.moves_since_progress(), 0);
+    /// chess.apply_white_and_respond("Qb1".parse().unwrap()).unwrap();
+    /// chess.apply_white_and_respond("Qc1".parse().unwrap()).unwrap();
+    /// assert_eq!(chess.moves_since_progress(), 2);
+    /// ```
+    pub fn moves_since_progress(&self) -> u64 {
+        self.moves_since_progress
+    }
+
+    /// Return the plies played so far, recorded only with [`GameConfig::record_transcript`].
+    /// A rejected move ends the transcript with no black reply; moves over the limit or after the
+    /// end of the game are not recorded.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameConfig};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// chess.play_with(&b"Qc1\nQc7\nQa7#\n"[..], Vec::new());
+    /// assert!(chess.transcript().is_empty());
+    ///
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// chess.set_config(GameConfig { record_transcript: true, ..GameConfig::default() });
+    /// chess.play_with(&b"Qc1\nQc7\nQa7#\n"[..], Vec::new());
+    /// let transcript = chess.transcript();
+    /// assert_eq!(transcript.len(), 3);
+    /// assert_eq!(transcript[0].white_move.to_string(), "Qc1");
+    /// assert_eq!(transcript[0].black_reply, Some(pos("b8")));
+    /// assert_eq!(transcript[0].resulting_fen.as_deref(), Some("1k6/8/1K6/8/8/8/8/2Q5 w - - 2 2"));
+    /// assert_eq!(transcript[2].black_reply, None);
+    /// assert_eq!(transcript[2].resulting_fen.as_deref(), Some("k7/Q7/1K6/8/8/8/8/8 b - - 5 3"));
+    /// ```
+    pub fn transcript(&self) -> &[TranscriptPly] {
+        &self.transcript
+    }
+
+    /// Return the last applied solution move in SAN, with `+` or `#` as the position after it
+    /// deserves regardless of how the solution announced it, or `None` before the first move.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 50);
+    /// assert_eq!(chess.last_white_move_san(), None);
+    /// chess.apply_white_and_respond("Qb1+".parse().unwrap()).unwrap();
+    /// assert_eq!(chess.last_white_move_san().as_deref(), Some("Qb1"));
+    /// chess.apply_white_and_respond("Qa2".parse().unwrap()).unwrap();
+    /// assert_eq!(chess.last_white_move_san().as_deref(), Some("Qa2+"));
+    ///
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// chess.play_with(&b"Qc1\nQc7\nQca7#\n"[..], Vec::new());
+    /// assert_eq!(chess.last_white_move_san().as_deref(), Some("Qa7#"));
+    /// ```
+    pub fn last_white_move_san(&self) -> Option<String> {
+        self.last_white_move
+            .map(|chess_move| chess_move.to_string())
+    }
+
+    /// Return whether the black king had a single legal move when it last moved, or `None` if
+    /// it has not moved yet.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// assert_eq!(chess.last_reply_was_forced(), None);
+    /// assert_eq!(chess.apply_white_and_respond("Qc1".parse().unwrap()).unwrap(), pos("b8"));
+    /// assert_eq!(chess.last_reply_was_forced(), Some(true));
+    ///
+    /// let mut chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 50);
+    /// chess.apply_white_and_respond("Qb1".parse().unwrap()).unwrap();
+    /// assert_eq!(chess.last_reply_was_forced(), Some(false));
+    /// ```
+    pub fn last_reply_was_forced(&self) -> Option<bool> {
+        self.last_reply_forced
+    }
+
+    /// Handle the solution claiming a draw by threefold repetition of the current position.
+    ///
+    /// The `draw` line is accepted in place of a move by [`Chess::play_with`]:
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let play = |input: &[u8]| Chess::new(pos("a1"), pos("a2"), pos("h8"), 50).play_with(input, Vec::new());
+    /// assert!(matches!(play(b"Qb1\nQa2\nQb1\nQa2\ndraw\n"), GameOver::Repetition));
+    /// match play(b"Qb1\nQa2\ndraw\n") {
+    ///     GameOver::WrongInput { kind: WrongInputKind::FalseClaim, error_message, .. } => {
+    ///         assert_eq!(error_message, "invalid draw claim");
+    ///     }
+    ///     other => panic!("unexpected outcome: {:?}", other),
+    /// }
+    /// ```
+    pub fn claim_draw_by_repetition(&mut self) -> GameOver {
+        self.finished = true;
+        if self.position_repetitions() >= 3 {
+            GameOver::Repetition
+        } else {
+            GameOver::WrongInput {
+                kind: WrongInputKind::FalseClaim,
+                error_message: "invalid draw claim".into(),
+                input: "draw".into(),
+            }
+        }
+    }
+
+    /// Wrap up the game with the given outcome.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = || Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// let wrong_input = || GameOver::WrongInput {
+    ///     kind: WrongInputKind::IllegalMove,
+    ///     error_message: "queen tried to do impossible move".into(),
+    ///     input: "Qb3".into(),
+    /// };
+    /// assert!(chess().finish(GameOver::Checkmate).solver_succeeded);
+    /// assert!(!chess().finish(wrong_input()).solver_succeeded);
+    /// assert!(!chess().finish(GameOver::SolverDisconnected).solver_succeeded);
+    /// assert!(!chess().finish(GameOver::TooManyMoves).solver_succeeded);
+    /// assert!(!chess().finish(GameOver::Draw).solver_succeeded);
+    /// assert!(!chess().finish(GameOver::Repetition).solver_succeeded);
+    /// assert!(!chess().finish(GameOver::Stalemate).solver_succeeded);
+    ///
+    /// let mut chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    /// let outcome = chess.play_with(&b"Qc8#\n"[..], Vec::new());
+    /// let result = chess.finish(outcome);
+    /// assert_eq!(result.total_moves, 1);
+    /// assert_eq!(result.to_string(), "Checkmate in 1 move");
+    /// ```
+    pub fn finish(self, outcome: GameOver) -> GameResult {
+        let solver_succeeded = match outcome {
+            GameOver::Checkmate => true,
+            GameOver::Stalemate => self.config.stalemate_counts_as_win,
+            _ => false,
+        };
+        if let Some(testlib_log) = &self.config.testlib_log {
+            testlib_log.write_line(format_args!(
+                "{} {}",
+                if solver_succeeded {
+                    "_ok"
+                } else {
+                    outcome.testlib_verdict()
+                },
+                summarize(&outcome, self.moves)
+            ));
+        }
+        GameResult {
+            solver_succeeded,
+            outcome,
+            total_moves: self.moves,
+            final_position_render: self
+                .renderer
+                .as_ref()
+                .map(|renderer| renderer.render(&self)),
+        }
+    }
+
+    pub fn play(&mut self) -> GameOver {
+        let stdin = std::io::stdin();
+        let stdout = std::io::stdout();
+        self.play_with(stdin.lock(), stdout.lock())
+    }
+
+    /// Play the game reading the solution moves from `input` and writing the interactor
+    /// responses to `output`.
+    ///
+    /// `output` is flushed after every line since the solution waits for it before making the
+    /// next move, so a custom writer must deliver the data on `flush` instead of holding it back.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// let mut output = Vec::new();
+    /// assert!(matches!(chess.play_with(&b""[..], &mut output), GameOver::SolverDisconnected));
+    /// assert_eq!(output, b"a2 h8 a4\n");
+    ///
+    /// #[derive(Default)]
+    /// struct FlushRecorder {
+    ///     data: Vec<u8>,
+    ///     flushed: Vec<usize>,
+    /// }
+    /// impl std::io::Write for FlushRecorder {
+    ///     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    ///         self.data.write(buf)
+    ///     }
+    ///     fn flush(&mut self) -> std::io::Result<()> {
+    ///         self.flushed.push(self.data.len());
+    ///         Ok(())
+    ///     }
+    /// }
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// let mut output = FlushRecorder::default();
+    /// chess.play_with(&b"Qc1\n"[..], &mut output);
+    /// assert_eq!(output.data, b"b6 c2 a8\nKb8\n");
+    /// assert_eq!(output.flushed, vec![9, 13]);
+    ///
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// match chess.play_with(&b"Qc\xff1\n"[..], Vec::new()) {
+    ///     GameOver::WrongInput { kind: WrongInputKind::Unparseable, error_message, input } => {
+    ///         assert_eq!(error_message, "non-ASCII input");
+    ///         assert_eq!(input, "Qc\u{fffd}1");
+    ///     }
+    ///     other => panic!("unexpected outcome: {:?}", other),
+    /// }
+    /// ```
+    pub fn play_with<R: BufRead, W: Write>(&mut self, mut input: R, mut output: W) -> GameOver {
+        let mut line_buffer = Vec::new();
+        let initial_state = if self.config.labeled_initial_state {
+            self.initial_state().to_labeled_string()
+        } else {
+            self.initial_state().to_string()
+        };
+        let initial_state = if self.config.print_moves_remaining {
+            format!("{} {}", initial_state, self.moves_remaining())
+        } else {
+            initial_state
+        };
+        writeln!(output, "{}", initial_state).expect("failed writing to the solution");
+        output.flush().expect("failed writing to the solution");
+        self.log_exchange('>', &initial_state);
+
+        if self.config.expect_echo_start {
+            let echo = match read_solution_line(
+                &mut input,
+                &mut line_buffer,
+                self.config.max_line_bytes,
+            ) {
+                Ok(line) => line.trim(),
+                Err(game_over) => return game_over,
+            };
+            self.log_exchange('<', echo);
+            if echo != initial_state {
+                return GameOver::WrongInput {
+                    kind: WrongInputKind::Unparseable,
+                    error_message: format!(
+                        "expected the echo of the initial line {:?}",
+                        initial_state
+                    ),
+                    input: echo.into(),
+                };
+            }
+        }
+
+        if self.config.expect_handshake {
+            let handshake = match read_solution_line(
+                &mut input,
+                &mut line_buffer,
+                self.config.max_line_bytes,
+            ) {
+                Ok(line) => line.trim(),
+                Err(game_over) => return game_over,
+            };
+            self.log_exchange('<', handshake);
+            if handshake != self.config.handshake_token {
+                return GameOver::WrongInput {
+                    kind: WrongInputKind::Unparseable,
+                    error_message: format!(
+                        "expected the {:?} handshake",
+                        self.config.handshake_token
+                    ),
+                    input: handshake.into(),
+                };
+            }
+        }
+
+        if self.moves_limit == 0 {
+            warn!("The moves limit is zero, so the game is over before the first solution move");
+        }
+
+        let mut parse_retries_left = self.config.parse_retries;
+        'lines: loop {
+            if self.moves_limit_reached() {
+                return GameOver::TooManyMoves;
+            }
+
+            let line = match read_solution_line(
+                &mut input,
+                &mut line_buffer,
+                self.config.max_line_bytes,
+            ) {
+                Ok(line) => line.trim(),
+                Err(game_over) => return game_over,
+            };
+            self.log_exchange('<', line);
+
+            if line == "draw" {
+                return self.claim_draw_by_repetition();
+            }
+
+            let batch: Vec<&str> = if self.config.multiple_moves_per_line && !line.is_empty() {
+                line.split_whitespace().collect()
+            } else {
+                vec![line]
+            };
+            let mut chess_moves = Vec::with_capacity(batch.len());
+            for move_input in batch {
+                match move_input.parse::<Move>() {
+                    Ok(chess_move) => chess_moves.push((move_input, chess_move)),
+                    Err(err) if self.config.lenient_diagnostics => {
+                        warn!("Skipping unparseable move {:?}: {}", move_input, err);
+                    }
+                    Err(err) if parse_retries_left > 0 => {
+                        parse_retries_left -= 1;
+                        warn!("Ignoring unparseable line {:?}: {}", line, err);
+                        continue 'lines;
+                    }
+                    Err(err) => {
+                        return GameOver::WrongInput {
+                            kind: WrongInputKind::Unparseable,
+                            error_message: err.into(),
+                            input: move_input.into(),
+                        };
+                    }
+                }
+            }
+
+            // A batch is played as a whole or not at all, so keep the game to roll back to
+            let batch_snapshot = if chess_moves.len() > 1 && !self.config.lenient_diagnostics {
+                Some(self.clone())
+            } else {
+                None
+            };
+            let mut black_replies = Vec::with_capacity(chess_moves.len());
+            let mut batch_outcome = None;
+            for (index, &(move_input, chess_move)) in chess_moves.iter().enumerate() {
+                if index > 0 && self.moves_limit_reached() {
+                    warn!(
+                        "Ignoring the moves over the limit: {:?}",
+                        batch_inputs(&chess_moves[index..])
+                    );
+                    batch_outcome = Some(GameOver::TooManyMoves);
+                    break;
+                }
+
+                let snapshot = if self.config.lenient_diagnostics {
+                    Some(self.clone())
+                } else {
+                    None
+                };
+                match self.respond(&chess_move, &move_input) {
+                    Ok(black_king_position) => {
+                        let black_reply = Move::black_king_reply(black_king_position).to_string();
+                        black_replies.push(if self.config.print_moves_remaining {
+                            format!("{} {}", black_reply, self.moves_remaining())
+                        } else {
+                            black_reply
+                        });
+                    }
+                    Err(GameOver::WrongInput { error_message, .. }) if snapshot.is_some() => {
+                        warn!("Skipping rejected move {:?}: {}", move_input, error_message);
+                        *self = snapshot.expect("snapshot is taken in the lenient mode");
+                    }
+                    Err(game_over @ GameOver::WrongInput { .. }) => {
+                        if let Some(batch_snapshot) = batch_snapshot {
+                            *self = batch_snapshot;
+                            self.finished = true;
+                        }
+                        return game_over;
+                    }
+                    Err(game_over) => {
+                        let moves_after_end = &chess_moves[index + 1..];
+                        if let Some(&(extra_input, _)) = moves_after_end.first() {
+                            // A move after the end of the game is illegal, so is the whole batch
+                            if let Some(batch_snapshot) = batch_snapshot {
+                                *self = batch_snapshot;
+                                self.finished = true;
+                                return GameOver::WrongInput {
+                                    kind: WrongInputKind::IllegalMove,
+                                    error_message: "game is already over".into(),
+                                    input: extra_input.into(),
+                                };
+                            }
+                            warn!(
+                                "Ignoring the moves after the end of the game: {:?}",
+                                batch_inputs(moves_after_end)
+                            );
+                        }
+                        batch_outcome = Some(game_over);
+                        break;
+                    }
+                }
+            }
+
+            if !black_replies.is_empty() {
+                parse_retries_left = self.config.parse_retries;
+            }
+            for black_reply in black_replies {
+                writeln!(output, "{}", black_reply).expect("failed writing to the solution");
+                output.flush().expect("failed writing to the solution");
+                self.log_exchange('>', &black_reply);
+            }
+            if let Some(game_over) = batch_outcome {
+                return game_over;
+            }
+        }
+    }
+
+    fn log_exchange(&self, direction: char, line: &str) {
+        info!(target: "game_log", "{}", line);
+        if let Some(testlib_log) = &self.config.testlib_log {
+            testlib_log.write_line(format_args!("{} {}", direction, line));
+        }
+    }
+
+    /// Apply the white move and let the black king reply, returning the new black king position
+    /// or the outcome if the game is over. This is [`Chess::play_with`] without the I/O.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    ///
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// assert_eq!(chess.apply_white_and_respond("Qc1".parse().unwrap()).unwrap(), pos("b8"));
+    /// assert_eq!(chess.moves(), 1);
+    ///
+    /// let mut chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    /// assert!(matches!(
+    ///     chess.apply_white_and_respond("Qc8#".parse().unwrap()),
+    ///     Err(GameOver::Checkmate)
+    /// ));
+    ///
+    /// let mut chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// assert!(matches!(
+    ///     chess.apply_white_and_respond("Qb3".parse().unwrap()),
+    ///     Err(GameOver::WrongInput { kind: WrongInputKind::IllegalMove, .. })
+    /// ));
+    /// ```
+    ///
+    /// Once the game is over, the following moves are rejected without changing the position:
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameOver, WrongInputKind};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    /// assert!(matches!(
+    ///     chess.apply_white_and_respond("Qc8#".parse().unwrap()),
+    ///     Err(GameOver::Checkmate)
+    /// ));
+    /// let position = chess.position_key();
+    /// match chess.apply_white_and_respond("Qc7".parse().unwrap()) {
+    ///     Err(GameOver::WrongInput { kind: WrongInputKind::IllegalMove, error_message, .. }) => {
+    ///         assert_eq!(error_message, "game is already over");
+    ///     }
+    ///     other => panic!("unexpected outcome: {:?}", other),
+    /// }
+    /// assert_eq!(chess.position_key(), position);
+    /// assert_eq!(chess.moves(), 1);
+    /// ```
+    pub fn apply_white_and_respond(&mut self, mv: Move) -> Result<ChessBoardPosition, GameOver> {
+        if !self.finished && self.moves_limit_reached() {
+            self.finished = true;
+            return Err(GameOver::TooManyMoves);
+        }
+        self.respond(&mv, &mv)
+    }
+
+    /// Apply the white moves one after another with [`Chess::apply_white_and_respond`] and return
+    /// the outcome together with the plies recorded as by [`Chess::transcript`]. Running out of
+    /// moves before the game is over counts as [`GameOver::SolverDisconnected`].
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GameOver, InitialState, Move};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let moves: Vec<Move> = ["Qc1", "Qc7", "Qa7#"].iter().map(|m| m.parse().unwrap()).collect();
+    ///
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// let (outcome, transcript) = chess.play_line(&moves);
+    /// assert!(matches!(outcome, GameOver::Checkmate));
+    /// let replies: Vec<_> = transcript.iter().map(|ply| ply.black_reply).collect();
+    /// assert_eq!(replies, [Some(pos("b8")), Some(pos("a8")), None]);
+    /// assert_eq!(transcript[2].white_move, moves[2]);
+    /// assert_eq!(transcript[2].resulting_fen.as_deref(), Some("k7/Q7/1K6/8/8/8/8/8 b - - 5 3"));
+    /// let start = InitialState::from_str("b6 c2 a8").unwrap();
+    /// assert!(matches!(Chess::validate_transcript(start, &transcript), Ok(GameOver::Checkmate)));
+    ///
+    /// let mut chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// let (outcome, transcript) = chess.play_line(&moves[..2]);
+    /// assert!(matches!(outcome, GameOver::SolverDisconnected));
+    /// assert_eq!(transcript.len(), 2);
+    /// assert!(chess.transcript().is_empty());
+    ///
+    /// let mut chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// let (outcome, transcript) = chess.play_line(&["Qb3".parse().unwrap()]);
+    /// assert!(matches!(outcome, GameOver::WrongInput { .. }));
+    /// assert_eq!(transcript[0].black_reply, None);
+    /// assert_eq!(transcript[0].resulting_fen.as_deref(), Some("7Q/8/8/8/k7/8/K7/8 w - - 0 1"));
+    /// ```
+    pub fn play_line(&mut self, moves: &[Move]) -> (GameOver, Vec<TranscriptPly>) {
+        let record_transcript = self.config.record_transcript;
+        self.config.record_transcript = true;
+        let start = self.transcript.len();
+        let outcome = moves
+            .iter()
+            .find_map(|&white_move| self.apply_white_and_respond(white_move).err())
+            .unwrap_or(GameOver::SolverDisconnected);
+        self.config.record_transcript = record_transcript;
+        let transcript = self.transcript[start..].to_vec();
+        if !record_transcript {
+            Arc::make_mut(&mut self.transcript).truncate(start);
+        }
+        (outcome, transcript)
+    }
+
+    fn respond(
+        &mut self,
+        chess_move: &Move,
+        input: &dyn std::fmt::Display,
+    ) -> Result<ChessBoardPosition, GameOver> {
+        if self.finished {
+            return Err(GameOver::WrongInput {
+                kind: WrongInputKind::IllegalMove,
+                error_message: "game is already over".into(),
+                input: input.to_string(),
+            });
+        }
+        let moves_before = self.moves;
+        let candidates = match self.apply_white_move(chess_move, input) {
+            Ok(candidates) => candidates,
+            Err(game_over) => {
+                self.finished = true;
+                // The game may end right after a legal white move, with black to move
+                self.record_ply(*chess_move, None, self.moves != moves_before);
+                return Err(game_over);
+            }
+        };
+        self.move_black_king(&candidates);
+        if let Err(game_over) = self.check_overlap() {
+            self.finished = true;
+            self.record_ply(*chess_move, None, false);
+            return Err(game_over);
+        }
+        self.record_ply(*chess_move, Some(self.black_king_position), false);
+        Ok(self.black_king_position)
+    }
+
+    fn record_ply(
+        &mut self,
+        white_move: Move,
+        black_reply: Option<ChessBoardPosition>,
+        black_to_move: bool,
+    ) {
+        if !self.config.record_transcript {
+            return;
+        }
+        let ply = TranscriptPly {
+            white_move,
+            black_reply,
+            resulting_fen: Some(self.fen(black_to_move)),
+        };
+        Arc::make_mut(&mut self.transcript).push(ply);
+    }
+
+    fn check_overlap(&self) -> Result<(), GameOver> {
+        let square = if self.white_king_position == self.white_queen_position
+            || self.white_king_position == self.black_king_position
+        {
+            self.white_king_position
+        } else if self.white_queen_position == self.black_king_position {
+            self.white_queen_position
+        } else {
+            return Ok(());
+        };
+        if cfg!(debug_assertions) {
+            panic!("two pieces on {} in {}", square, self.to_fen());
+        }
+        warn!("Two pieces on {} in {}", square, self.to_fen());
+        Err(GameOver::PiecesOverlap { square })
+    }
+
+    /// Replay a recorded game checking that every black king reply was a legal move, and return
+    /// the outcome of the game. Ply indices in the errors are zero-based.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{
+    ///     Chess, ChessBoardPosition, GameOver, InitialState, Move, TranscriptPly, ValidationError,
+    /// };
+    /// fn pos(s: &str) -> ChessBoardPosition {
+    ///     ChessBoardPosition::from_str(s).unwrap()
+    /// }
+    /// let ply = |white_move, black_reply: Option<&str>| TranscriptPly {
+    ///     white_move: Move::from_str(white_move).unwrap(),
+    ///     black_reply: black_reply.map(pos),
+    ///     resulting_fen: None,
+    /// };
+    /// let start = InitialState::from_str("b6 c2 a8").unwrap();
+    /// let mut plies = vec![ply("Qc1", Some("b8")), ply("Qc7", Some("a8")), ply("Qa7#", None)];
+    /// assert!(matches!(Chess::validate_transcript(start, &plies), Ok(GameOver::Checkmate)));
+    ///
+    /// plies[0].black_reply = Some(pos("b7"));
+    /// assert_eq!(
+    ///     Chess::validate_transcript(start, &plies).unwrap_err(),
+    ///     ValidationError::IllegalBlackReply { ply: 0 }
+    /// );
+    /// assert_eq!(
+    ///     Chess::validate_transcript(start, &plies[..0]).unwrap_err(),
+    ///     ValidationError::Unfinished
+    /// );
+    /// ```
+    pub fn validate_transcript(
+        start: InitialState,
+        plies: &[TranscriptPly],
+    ) -> Result<GameOver, ValidationError> {
+        let mut chess = Chess::new(
+            start.white_king,
+            start.white_queen,
+            start.black_king,
+            u64::MAX,
+        );
+        for (ply_index, ply) in plies.iter().enumerate() {
+            match chess.apply_white_move(&ply.white_move, &ply.white_move) {
+                Ok(candidates) => match ply.black_reply {
+                    None => return Err(ValidationError::MissingBlackReply { ply: ply_index }),
+                    Some(black_reply) if !candidates.contains(&black_reply) => {
+                        return Err(ValidationError::IllegalBlackReply { ply: ply_index });
+                    }
+                    Some(black_reply) => chess.black_king_position = black_reply,
+                },
+                Err(game_over) => {
+                    if ply.black_reply.is_some() {
+                        return Err(ValidationError::UnexpectedBlackReply { ply: ply_index });
+                    }
+                    if ply_index + 1 < plies.len() {
+                        return Err(ValidationError::PlyAfterGameOver { ply: ply_index + 1 });
+                    }
+                    return Ok(game_over);
+                }
+            }
+        }
+        Err(ValidationError::Unfinished)
+    }
+
+    fn moves_limit_reached(&self) -> bool {
+        self.moves_remaining() == 0
+    }
+
+    fn try_apply_move(&mut self, chess_move: &Move) -> Result<(), &'static str> {
+        let chess_piece_move = chess_move.to;
+        if let Some(disambiguation) = chess_move.disambiguation {
+            let source = match chess_move.piece {
+                ChessPiece::King => self.white_king_position,
+                ChessPiece::Queen => self.white_queen_position,
+            };
+            if !disambiguation.matches(&source) {
+                return Err("disambiguation does not match the piece position");
+            }
+        }
+        match chess_move.piece {
+            #[cfg(not(feature = "king-moves-enabled"))]
+            ChessPiece::King => return Err("king moves are not allowed"),
+            #[cfg(feature = "king-moves-enabled")]
+            ChessPiece::King => {
+                debug!(
+                    "Tring to move white king from {} to {}",
+                    self.white_king_position, chess_piece_move
+                );
+                self.policy
+                    .validate_king_move(&self.position_key(), chess_piece_move)?;
+                self.white_king_position = chess_piece_move;
+            }
+            ChessPiece::Queen => {
+                debug!(
+                    "Tring to move white queen from {} to {}",
+                    self.white_queen_position, chess_piece_move
+                );
+                self.policy
+                    .validate_queen_move(&self.position_key(), chess_piece_move)?;
+                self.white_queen_position = chess_piece_move;
             }
-            board[row][column] = ChessBoardCell::Attackable;
         }
+        Ok(())
+    }
 
-        // Mark attackable cells by white queen down left
-        let mut column = usize::from(self.white_queen_position.column);
-        let mut row = usize::from(self.white_queen_position.row);
-        while row > 0 && column > 0 {
-            column -= 1;
-            row -= 1;
-            if let ChessBoardCell::King = board[row][column] {
+    /// Check the white moves one after another on a copy of the game, leaving the black king in
+    /// place, and report the legality of each of them up to the first illegal one.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, Move};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// let moves: Vec<Move> = ["Qh4", "Qb3", "Qh1"].iter().map(|m| m.parse().unwrap()).collect();
+    /// assert_eq!(
+    ///     chess.dry_run(&moves),
+    ///     vec![Ok(()), Err("queen tried to do impossible move".to_owned())]
+    /// );
+    /// assert_eq!(chess.dry_run(&moves[..1]), vec![Ok(())]);
+    ///
+    /// // Moving a piece to its own square is rejected the same way for the queen and the king
+    /// let null_move = |m: &str| chess.dry_run(&[m.parse().unwrap()]);
+    /// assert_eq!(null_move("Qh8"), vec![Err("piece was not moved".to_owned())]);
+    /// if cfg!(feature = "king-moves-enabled") {
+    ///     assert_eq!(null_move("Ka2"), vec![Err("piece was not moved".to_owned())]);
+    /// }
+    /// assert!(chess.same_position(&Chess::new(pos("a2"), pos("h8"), pos("a4"), 50)));
+    /// ```
+    pub fn dry_run(&self, moves: &[Move]) -> Vec<Result<(), String>> {
+        let mut chess = self.clone();
+        let mut report = Vec::with_capacity(moves.len());
+        for chess_move in moves {
+            let result = chess.try_apply_move(chess_move).map_err(String::from);
+            let is_illegal = result.is_err();
+            report.push(result);
+            if is_illegal {
                 break;
             }
-            board[row][column] = ChessBoardCell::Attackable;
         }
+        report
+    }
 
-        // Mark attackable cells by white queen down right
-        let mut column = usize::from(self.white_queen_position.column);
-        let mut row = usize::from(self.white_queen_position.row);
-        while row > 0 && column < 7 {
-            column += 1;
-            row -= 1;
-            if let ChessBoardCell::King = board[row][column] {
-                break;
+    /// Return all the legal white moves in the current position.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, ChessPiece};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 50);
+    /// let queen_moves: Vec<_> = chess
+    ///     .legal_white_moves()
+    ///     .into_iter()
+    ///     .filter(|chess_move| chess_move.piece == ChessPiece::Queen)
+    ///     .collect();
+    /// // a3-a8, b2-h2, b3-g8 and b1
+    /// assert_eq!(queen_moves.len(), 6 + 7 + 6 + 1);
+    /// ```
+    pub fn legal_white_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for row in 0..BOARD_SIZE {
+            for column in 0..BOARD_SIZE {
+                let chess_move = Move {
+                    piece: ChessPiece::Queen,
+                    disambiguation: None,
+                    to: ChessBoardPosition { row, column },
+                    claim: None,
+                };
+                if self.clone().try_apply_move(&chess_move).is_ok() {
+                    moves.push(chess_move);
+                }
             }
-            board[row][column] = ChessBoardCell::Attackable;
         }
+        if cfg!(feature = "king-moves-enabled") {
+            moves.extend(self.legal_white_king_moves().into_iter().map(|to| Move {
+                piece: ChessPiece::King,
+                disambiguation: None,
+                to,
+                claim: None,
+            }));
+        }
+        moves
+    }
 
-        // Mark attackable cells by white queen up left
-        let mut column = usize::from(self.white_queen_position.column);
-        let mut row = usize::from(self.white_queen_position.row);
-        while row < 7 && column > 0 {
-            column -= 1;
-            row += 1;
-            if let ChessBoardCell::King = board[row][column] {
-                break;
+    /// Return every legal white move together with a copy of the game after the move, before
+    /// the black king replies.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a1"), pos("a2"), pos("h8"), 50);
+    /// let successors = chess.white_successors();
+    /// assert_eq!(successors.len(), chess.legal_white_moves().len());
+    /// let (chess_move, successor) = &successors[0];
+    /// assert_eq!(chess_move.to_string(), "Qb1");
+    /// assert_eq!(successor.initial_state().to_string(), "a1 b1 h8");
+    /// assert_eq!(successor.moves(), 1);
+    /// ```
+    pub fn white_successors(&self) -> Vec<(Move, Chess)> {
+        self.legal_white_moves()
+            .into_iter()
+            .map(|chess_move| {
+                let mut successor = self.clone();
+                successor
+                    .try_apply_move(&chess_move)
+                    .expect("legal white moves must apply");
+                successor.moves += 1;
+                (chess_move, successor)
+            })
+            .collect()
+    }
+
+    /// Return the squares the white king could move to according to the [`MovePolicy`], with
+    /// the [`StandardPolicy`] the adjacent squares which are neither occupied by the queen nor
+    /// next to the black king. The result does not depend on whether the king moves are enabled.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert_eq!(Chess::new(pos("a1"), pos("h8"), pos("a3"), 50).legal_white_king_moves(), vec![pos("b1")]);
+    /// assert!(Chess::new(pos("a1"), pos("b1"), pos("a3"), 50).legal_white_king_moves().is_empty());
+    /// assert_eq!(Chess::new(pos("e4"), pos("e5"), pos("h8"), 50).legal_white_king_moves().len(), 7);
+    /// ```
+    ///
+    /// A custom policy restricts the king moves the same way in both the listed moves and the
+    /// moves the game accepts:
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{
+    ///     Chess, ChessBoardPosition, ChessPiece, Move, MovePolicy, PositionKey, StandardPolicy,
+    /// };
+    ///
+    /// /// Lets the white king move forward only
+    /// struct ForwardKing;
+    ///
+    /// impl MovePolicy for ForwardKing {
+    ///     fn validate_queen_move(
+    ///         &self,
+    ///         position: &PositionKey,
+    ///         to: ChessBoardPosition,
+    ///     ) -> Result<(), &'static str> {
+    ///         StandardPolicy.validate_queen_move(position, to)
+    ///     }
+    ///
+    ///     fn validate_king_move(
+    ///         &self,
+    ///         position: &PositionKey,
+    ///         to: ChessBoardPosition,
+    ///     ) -> Result<(), &'static str> {
+    ///         StandardPolicy.validate_king_move(position, to)?;
+    ///         if to.row != position.white_king_position.row + 1 {
+    ///             return Err("king may only move forward");
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let mut chess = Chess::new(pos("e4"), pos("e5"), pos("h8"), 50);
+    /// chess.set_policy(ForwardKing);
+    /// let king_moves = chess.legal_white_king_moves();
+    /// assert_eq!(king_moves, [pos("d5"), pos("f5")]);
+    /// if cfg!(feature = "king-moves-enabled") {
+    ///     for row in 0..8 {
+    ///         for column in 0..8 {
+    ///             let to = ChessBoardPosition { row, column };
+    ///             let king_move = Move { piece: ChessPiece::King, disambiguation: None, to, claim: None };
+    ///             let accepted = chess.dry_run(&[king_move]).remove(0).is_ok();
+    ///             assert_eq!(accepted, king_moves.contains(&to));
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn legal_white_king_moves(&self) -> Vec<ChessBoardPosition> {
+        let position_key = self.position_key();
+        let mut moves = Vec::new();
+        for row in 0..BOARD_SIZE {
+            for column in 0..BOARD_SIZE {
+                let to = ChessBoardPosition { row, column };
+                if self.policy.validate_king_move(&position_key, to).is_ok() {
+                    moves.push(to);
+                }
+            }
+        }
+        moves
+    }
+
+    /// Check whether the black king is attacked by the white pieces.
+    pub fn black_in_check(&self) -> bool {
+        let board = self.attack_map();
+        matches!(
+            board[usize::from(self.black_king_position.row)]
+                [usize::from(self.black_king_position.column)],
+            ChessBoardCell::Attackable
+        )
+    }
+
+    /// Check whether the white queen attacks the black king directly, i.e. they are on the same
+    /// line with no white king between them.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert!(Chess::new(pos("a1"), pos("d1"), pos("d8"), 50).queen_checks_black_king());
+    /// assert!(Chess::new(pos("a1"), pos("b2"), pos("h8"), 50).queen_checks_black_king());
+    /// assert!(!Chess::new(pos("d4"), pos("d1"), pos("d8"), 50).queen_checks_black_king());
+    /// assert!(!Chess::new(pos("a1"), pos("c1"), pos("d8"), 50).queen_checks_black_king());
+    /// ```
+    pub fn queen_checks_black_king(&self) -> bool {
+        self.white_queen_position
+            .squares_between(&self.black_king_position)
+            .map(|between| !between.contains(&self.white_king_position))
+            .unwrap_or(false)
+    }
+
+    /// Return the king distances from the black king to the white king and to the white queen.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert_eq!(Chess::new(pos("b6"), pos("c2"), pos("a8"), 50).black_king_proximity(), (2, 6));
+    /// assert_eq!(Chess::new(pos("e1"), pos("d7"), pos("e8"), 50).black_king_proximity(), (7, 1));
+    /// ```
+    pub fn black_king_proximity(&self) -> (u8, u8) {
+        (
+            self.black_king_position
+                .king_distance(&self.white_king_position),
+            self.black_king_position
+                .king_distance(&self.white_queen_position),
+        )
+    }
+
+    /// Return the mean row and the mean column of the three pieces, zero-based like
+    /// [`ChessBoardPosition`].
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert_eq!(Chess::new(pos("a1"), pos("a1"), pos("a1"), 50).centroid(), (0.0, 0.0));
+    /// assert_eq!(Chess::new(pos("a2"), pos("h8"), pos("a5"), 50).centroid(), (4.0, 7.0 / 3.0));
+    /// ```
+    pub fn centroid(&self) -> (f32, f32) {
+        let pieces = [
+            self.white_king_position,
+            self.white_queen_position,
+            self.black_king_position,
+        ];
+        let mean = |coordinate: fn(&ChessBoardPosition) -> u8| {
+            pieces
+                .iter()
+                .map(|piece| f32::from(coordinate(piece)))
+                .sum::<f32>()
+                / pieces.len() as f32
+        };
+        (mean(|piece| piece.row), mean(|piece| piece.column))
+    }
+
+    /// Check whether the kings stand in direct opposition, i.e. on the same row or column with
+    /// exactly one empty square between them.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert!(Chess::new(pos("b6"), pos("d7"), pos("b8"), 50).in_opposition());
+    /// assert!(Chess::new(pos("c8"), pos("h1"), pos("a8"), 50).in_opposition());
+    /// assert!(!Chess::new(pos("c6"), pos("d7"), pos("a8"), 50).in_opposition());
+    /// assert!(!Chess::new(pos("b5"), pos("d7"), pos("b8"), 50).in_opposition());
+    /// assert!(!Chess::new(pos("b6"), pos("b7"), pos("b8"), 50).in_opposition());
+    /// ```
+    pub fn in_opposition(&self) -> bool {
+        match self
+            .white_king_position
+            .squares_between(&self.black_king_position)
+        {
+            Ok(between) => {
+                between.len() == 1
+                    && (self.white_king_position.row == self.black_king_position.row
+                        || self.white_king_position.column == self.black_king_position.column)
+                    && !between.contains(&self.white_queen_position)
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Check whether the white queen is a knight's move away from the black king, the classic
+    /// setup for the mating net.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert!(Chess::new(pos("b6"), pos("d7"), pos("b8"), 50).queen_knight_away());
+    /// assert!(Chess::new(pos("a6"), pos("c7"), pos("a8"), 50).queen_knight_away());
+    /// assert!(!Chess::new(pos("a6"), pos("c8"), pos("a8"), 50).queen_knight_away());
+    /// assert!(!Chess::new(pos("a6"), pos("d6"), pos("a8"), 50).queen_knight_away());
+    /// ```
+    pub fn queen_knight_away(&self) -> bool {
+        let rows = self
+            .white_queen_position
+            .row
+            .abs_diff(self.black_king_position.row);
+        let columns = self
+            .white_queen_position
+            .column
+            .abs_diff(self.black_king_position.column);
+        matches!((rows, columns), (1, 2) | (2, 1))
+    }
+
+    /// Check whether the black king is in check and has no legal moves.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert!(Chess::new(pos("b6"), pos("c8"), pos("a8"), 50).is_checkmate());
+    /// assert!(!Chess::new(pos("b6"), pos("a1"), pos("a8"), 50).is_checkmate());
+    /// assert!(!Chess::new(pos("b6"), pos("c7"), pos("a8"), 50).is_checkmate());
+    /// ```
+    pub fn is_checkmate(&self) -> bool {
+        self.black_in_check() && self.black_reply_candidates().is_empty()
+    }
+
+    /// Explain a checkmate: which piece gives check and what covers each square around the
+    /// black king. Returns `None` if the position is not a checkmate.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{
+    ///     Chess, ChessBoardPosition, ChessPiece, Direction, EscapeSquareCover, MateExplanation,
+    /// };
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let attacked = |square, by: &[ChessPiece]| EscapeSquareCover::Attacked { square: pos(square), by: by.to_vec() };
+    /// assert_eq!(
+    ///     Chess::new(pos("b6"), pos("c8"), pos("a8"), 50).is_mate_explained(),
+    ///     Some(MateExplanation {
+    ///         checking_piece: ChessPiece::Queen,
+    ///         escape_squares: vec![
+    ///             EscapeSquareCover::OffBoard(Direction::North),
+    ///             EscapeSquareCover::OffBoard(Direction::NorthEast),
+    ///             attacked("b8", &[ChessPiece::Queen]),
+    ///             attacked("b7", &[ChessPiece::King, ChessPiece::Queen]),
+    ///             attacked("a7", &[ChessPiece::King]),
+    ///             EscapeSquareCover::OffBoard(Direction::SouthWest),
+    ///             EscapeSquareCover::OffBoard(Direction::West),
+    ///             EscapeSquareCover::OffBoard(Direction::NorthWest),
+    ///         ],
+    ///     })
+    /// );
+    /// assert_eq!(Chess::new(pos("b6"), pos("c7"), pos("a8"), 50).is_mate_explained(), None);
+    /// ```
+    pub fn is_mate_explained(&self) -> Option<MateExplanation> {
+        if !self.is_checkmate() {
+            return None;
+        }
+        let escape_squares = Direction::ALL
+            .iter()
+            .map(|&direction| {
+                let (row_direction, column_direction) = direction.delta();
+                let row = i16::from(self.black_king_position.row) + i16::from(row_direction);
+                let column =
+                    i16::from(self.black_king_position.column) + i16::from(column_direction);
+                if row < 0
+                    || row > i16::from(BOARD_LAST_INDEX)
+                    || column < 0
+                    || column > i16::from(BOARD_LAST_INDEX)
+                {
+                    return EscapeSquareCover::OffBoard(direction);
+                }
+                let square = ChessBoardPosition {
+                    row: row as u8,
+                    column: column as u8,
+                };
+                EscapeSquareCover::Attacked {
+                    square,
+                    by: self.attackers_of(&square),
+                }
+            })
+            .collect();
+        Some(MateExplanation {
+            // The white king can never give check
+            checking_piece: ChessPiece::Queen,
+            escape_squares,
+        })
+    }
+
+    /// Return the white pieces attacking the square, the king first. The black king does not
+    /// block the queen, so the squares behind it are attacked too.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, ChessPiece};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("b6"), pos("c8"), pos("a8"), 50);
+    /// assert_eq!(chess.attackers_of(&pos("b7")), [ChessPiece::King, ChessPiece::Queen]);
+    /// assert_eq!(chess.attackers_of(&pos("a7")), [ChessPiece::King]);
+    /// assert_eq!(chess.attackers_of(&pos("b8")), [ChessPiece::Queen]);
+    /// assert!(chess.attackers_of(&pos("h1")).is_empty());
+    /// ```
+    pub fn attackers_of(&self, pos: &ChessBoardPosition) -> Vec<ChessPiece> {
+        let mut attackers = Vec::new();
+        if let Ok((1, _)) = self.white_king_position.queen_distance(pos) {
+            attackers.push(ChessPiece::King);
+        }
+        if Direction::ALL.iter().any(|&direction| {
+            self.queen_ray_squares(direction)
+                .any(|square| square == *pos)
+        }) {
+            attackers.push(ChessPiece::Queen);
+        }
+        attackers
+    }
+
+    /// Return all the legal white moves which checkmate the black king, announced with `#`.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, Move};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    /// assert_eq!(chess.mating_moves(), [Move::from_str("Qc8#").unwrap()]);
+    /// let chess = Chess::new(pos("a2"), pos("h8"), pos("a4"), 50);
+    /// assert!(chess.mating_moves().is_empty());
+    /// ```
+    pub fn mating_moves(&self) -> Vec<Move> {
+        self.legal_white_moves()
+            .into_iter()
+            .filter(|chess_move| {
+                let mut chess = self.clone();
+                chess.try_apply_move(chess_move).is_ok() && chess.is_checkmate()
+            })
+            .map(|chess_move| Move {
+                claim: Some(MoveClaim::Checkmate),
+                ..chess_move
+            })
+            .collect()
+    }
+
+    /// Check whether the solution can checkmate with its next move.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert!(Chess::new(pos("b6"), pos("c1"), pos("a8"), 50).mate_in_one_available());
+    /// assert!(!Chess::new(pos("a2"), pos("h8"), pos("a4"), 50).mate_in_one_available());
+    /// ```
+    pub fn mate_in_one_available(&self) -> bool {
+        !self.mating_moves().is_empty()
+    }
+
+    /// Return the moves checkmating right away, the same as [`Chess::mating_moves`].
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, Move};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("b6"), pos("c1"), pos("a8"), 50);
+    /// assert_eq!(chess.mate_in_one_moves(), [Move::from_str("Qc8#").unwrap()]);
+    /// assert!(Chess::new(pos("a2"), pos("h8"), pos("a4"), 50).mate_in_one_moves().is_empty());
+    /// ```
+    pub fn mate_in_one_moves(&self) -> Vec<Move> {
+        self.mating_moves()
+    }
+
+    /// Return all the (from, to) pairs of the black king moves to the squares not attacked by
+    /// white pieces.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a1"), pos("h8"), pos("e4"), 50);
+    /// let candidates = chess.black_reply_candidates();
+    /// assert_eq!(candidates.len(), 6);
+    /// assert!(candidates.iter().all(|(from, _)| *from == pos("e4")));
+    /// assert!(!candidates.iter().any(|(_, to)| *to == pos("d4") || *to == pos("e5")));
+    /// ```
+    pub fn black_reply_candidates(&self) -> Vec<(ChessBoardPosition, ChessBoardPosition)> {
+        self.black_king_moves(&self.attack_map())
+            .into_iter()
+            .map(|new_position| (self.black_king_position, new_position))
+            .collect()
+    }
+
+    /// Check whether the black king may move from its current square to `to`: the target must
+    /// be a neighbouring square on the board which is not attacked by white pieces.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a1"), pos("h8"), pos("e4"), 50);
+    /// assert!(chess.is_legal_black_move(&pos("d3")));
+    /// assert!(chess.is_legal_black_move(&pos("f5")));
+    /// // Attacked by the queen
+    /// assert!(!chess.is_legal_black_move(&pos("d4")));
+    /// assert!(!chess.is_legal_black_move(&pos("e5")));
+    /// // Not a king move
+    /// assert!(!chess.is_legal_black_move(&pos("e6")));
+    /// assert!(!chess.is_legal_black_move(&pos("e4")));
+    /// assert!(!chess.is_legal_black_move(&ChessBoardPosition { row: 8, column: 4 }));
+    /// ```
+    pub fn is_legal_black_move(&self, to: &ChessBoardPosition) -> bool {
+        if to.row >= BOARD_SIZE
+            || to.column >= BOARD_SIZE
+            || self.black_king_position.king_distance(to) != 1
+        {
+            return false;
+        }
+        matches!(
+            self.attack_map()[usize::from(to.row)][usize::from(to.column)],
+            ChessBoardCell::Available
+        )
+    }
+
+    /// Return the square the given strategy would move the black king to if it were black to
+    /// move in the current position, or `None` if the black king has no legal moves.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, GreedyStrategy, NaiveStrategy};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("a1"), pos("b3"), pos("c7"), 50);
+    /// assert_eq!(chess.preview_black_reply(&NaiveStrategy), Some(pos("d8")));
+    /// assert_eq!(chess.preview_black_reply(&GreedyStrategy), Some(pos("d7")));
+    /// assert_eq!(Chess::new(pos("b6"), pos("c7"), pos("a8"), 50).preview_black_reply(&GreedyStrategy), None);
+    /// ```
+    pub fn preview_black_reply(
+        &self,
+        strategy: &dyn BlackKingStrategy,
+    ) -> Option<ChessBoardPosition> {
+        let candidates = self.black_king_moves(&self.attack_map());
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(strategy.choose(self, &candidates))
+    }
+
+    /// Return the black king replies which increase its distance from the nearest corner.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("h8"), pos("h6"), pos("b1"), 50);
+    /// assert_eq!(chess.black_reply_candidates().len(), 4);
+    /// assert_eq!(chess.centralizing_moves(), vec![pos("c2")]);
+    /// ```
+    pub fn centralizing_moves(&self) -> Vec<ChessBoardPosition> {
+        let corner_distance = self.black_king_position.corner_distance();
+        self.black_king_moves(&self.attack_map())
+            .into_iter()
+            .filter(|new_position| new_position.corner_distance() > corner_distance)
+            .collect()
+    }
+
+    /// Return the squares the white queen attacks in the given direction, up to the board edge
+    /// or the white king, whichever comes first.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, Direction};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("d6"), pos("d4"), pos("h8"), 50);
+    /// assert_eq!(chess.queen_ray(Direction::North), vec![pos("d5")]);
+    /// assert_eq!(chess.queen_ray(Direction::South), vec![pos("d3"), pos("d2"), pos("d1")]);
+    /// assert_eq!(
+    ///     chess.queen_ray(Direction::NorthEast),
+    ///     vec![pos("e5"), pos("f6"), pos("g7"), pos("h8")]
+    /// );
+    /// assert_eq!(chess.queen_ray(Direction::West), vec![pos("c4"), pos("b4"), pos("a4")]);
+    /// ```
+    pub fn queen_ray(&self, direction: Direction) -> Vec<ChessBoardPosition> {
+        self.queen_ray_squares(direction).collect()
+    }
+
+    fn queen_ray_squares(
+        &self,
+        direction: Direction,
+    ) -> impl Iterator<Item = ChessBoardPosition> + '_ {
+        let (row_direction, column_direction) = direction.delta();
+        std::iter::successors(Some(self.white_queen_position), move |position| {
+            let row = i16::from(position.row) + i16::from(row_direction);
+            let column = i16::from(position.column) + i16::from(column_direction);
+            if row < 0
+                || row > i16::from(BOARD_LAST_INDEX)
+                || column < 0
+                || column > i16::from(BOARD_LAST_INDEX)
+            {
+                return None;
+            }
+            Some(ChessBoardPosition {
+                row: row as u8,
+                column: column as u8,
+            })
+        })
+        .skip(1)
+        .take_while(move |position| *position != self.white_king_position)
+    }
+
+    /// Return the number of squares in the region the black king could walk around without
+    /// stepping on attacked squares if white passed, including its current square.
+    ///
+    /// The area steadily shrinking from move to move indicates the solution makes progress.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert_eq!(Chess::new(pos("h1"), pos("c6"), pos("a7"), 50).confinement_area(), 2);
+    /// assert_eq!(Chess::new(pos("b6"), pos("c7"), pos("a8"), 50).confinement_area(), 1);
+    /// // e7-h7, d8 and f8-h8 behind the 6th rank cut off by the queen
+    /// assert_eq!(Chess::new(pos("a1"), pos("c6"), pos("g7"), 50).confinement_area(), 8);
+    /// ```
+    pub fn confinement_area(&self) -> u8 {
+        self.black_king_region().len() as u8
+    }
+
+    /// Return the squares the black king could eventually reach without stepping on attacked
+    /// squares if white passed, including its current square, from a1 to h8.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("h1"), pos("c6"), pos("a7"), 50);
+    /// assert_eq!(chess.black_king_region(), [pos("a7"), pos("b8")]);
+    /// // b3-h8 except the b3-g8 diagonal, while c1-h1 is cut off by the queen on the 2nd rank
+    /// let region = Chess::new(pos("a1"), pos("a2"), pos("e5"), 50).black_king_region();
+    /// assert_eq!(region.len(), 6 * 7 - 6);
+    /// assert_eq!(region[0], pos("c3"));
+    /// assert!(!region.contains(&pos("h1")));
+    /// ```
+    pub fn black_king_region(&self) -> Vec<ChessBoardPosition> {
+        let board = self.attack_map();
+        let mut visited = [[false; BOARD_SIZE as usize]; BOARD_SIZE as usize];
+        visited[usize::from(self.black_king_position.row)]
+            [usize::from(self.black_king_position.column)] = true;
+        let mut region = vec![self.black_king_position];
+        let mut stack = vec![self.black_king_position];
+        while let Some(position) = stack.pop() {
+            let neighbours = Chess {
+                black_king_position: position,
+                ..self.clone()
+            }
+            .black_king_moves(&board);
+            for neighbour in neighbours {
+                let cell_visited =
+                    &mut visited[usize::from(neighbour.row)][usize::from(neighbour.column)];
+                if !*cell_visited {
+                    *cell_visited = true;
+                    region.push(neighbour);
+                    stack.push(neighbour);
+                }
+            }
+        }
+        region.sort_by_key(|position| (position.row, position.column));
+        region
+    }
+
+    /// Suggest the queen move which leaves the black king the smallest
+    /// [`confinement_area`](Chess::confinement_area), preferring moves which keep the queen next
+    /// to the white king on ties. Moves which hang the queen or stalemate are never suggested.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let chess = Chess::new(pos("b6"), pos("c2"), pos("a8"), 50);
+    /// assert_eq!(chess.best_confining_queen_move().unwrap().to_string(), "Qc8");
+    /// // Qd7 would leave fewer squares but hangs the queen
+    /// let chess = Chess::new(pos("e1"), pos("d1"), pos("e8"), 50);
+    /// assert_eq!(chess.best_confining_queen_move().unwrap().to_string(), "Qd6");
+    /// ```
+    pub fn best_confining_queen_move(&self) -> Option<Move> {
+        let mut best: Option<(Move, (u8, bool))> = None;
+        for (chess_move, successor) in self.white_successors() {
+            if chess_move.piece != ChessPiece::Queen {
+                continue;
+            }
+            let defended = matches!(
+                successor
+                    .white_king_position
+                    .queen_distance(&successor.white_queen_position),
+                Ok((1, _))
+            );
+            let hanging = !defended
+                && matches!(
+                    successor
+                        .black_king_position
+                        .queen_distance(&successor.white_queen_position),
+                    Ok((1, _))
+                );
+            let stalemate = !successor.black_in_check()
+                && successor
+                    .black_king_moves(&successor.attack_map())
+                    .is_empty();
+            if hanging || stalemate {
+                continue;
+            }
+            let score = (successor.confinement_area(), !defended);
+            match best {
+                Some((_, best_score)) if best_score <= score => (),
+                _ => best = Some((chess_move, score)),
+            }
+        }
+        best.map(|(chess_move, _)| chess_move)
+    }
+
+    /// Estimate the direction the black king is being driven in: the prevailing direction of
+    /// its legal moves, since the white pieces take away the squares on the opposite side.
+    ///
+    /// Returns `None` if the black king has no legal moves or they are balanced.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, Direction};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// // The queen holds the c-file, so the black king is pushed to the a-file
+    /// assert_eq!(Chess::new(pos("h1"), pos("c8"), pos("b4"), 50).pressure_direction(), Some(Direction::West));
+    /// assert_eq!(Chess::new(pos("h1"), pos("c6"), pos("a7"), 50).pressure_direction(), Some(Direction::NorthEast));
+    /// assert_eq!(Chess::new(pos("b6"), pos("c7"), pos("a8"), 50).pressure_direction(), None);
+    /// ```
+    pub fn pressure_direction(&self) -> Option<Direction> {
+        let (mut row_sum, mut column_sum) = (0i16, 0i16);
+        for position in self.black_king_moves(&self.attack_map()) {
+            row_sum += i16::from(position.row) - i16::from(self.black_king_position.row);
+            column_sum += i16::from(position.column) - i16::from(self.black_king_position.column);
+        }
+        Direction::from_delta((row_sum.signum() as i8, column_sum.signum() as i8))
+    }
+
+    /// Return the squares not occupied by the white pieces where a black king would be in
+    /// check. The current black king position does not affect the result.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// let squares = Chess::new(pos("a1"), pos("h8"), pos("e4"), 50).checking_squares();
+    /// // a8-g8, h1-h7, b2-g7 and a2, b1 next to the white king
+    /// assert_eq!(squares.len(), 7 + 7 + 6 + 2);
+    /// assert!(!squares.contains(&pos("a1")));
+    /// assert!(!squares.contains(&pos("h8")));
+    /// ```
+    pub fn checking_squares(&self) -> Vec<ChessBoardPosition> {
+        let board = self.attack_map();
+        let mut squares = Vec::new();
+        for row in 0..BOARD_SIZE {
+            for column in 0..BOARD_SIZE {
+                let position = ChessBoardPosition { row, column };
+                if position == self.white_queen_position {
+                    continue;
+                }
+                if let ChessBoardCell::Attackable = board[usize::from(row)][usize::from(column)] {
+                    squares.push(position);
+                }
+            }
+        }
+        squares
+    }
+
+    /// Return the directions in which the white king stands on the queen's line and cuts her
+    /// ray short.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use chess_interactor::{Chess, ChessBoardPosition, Direction};
+    /// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+    /// assert_eq!(Chess::new(pos("d6"), pos("d4"), pos("h8"), 50).blocked_rays(), vec![Direction::North]);
+    /// assert_eq!(Chess::new(pos("a1"), pos("d4"), pos("h8"), 50).blocked_rays(), vec![Direction::SouthWest]);
+    /// assert!(Chess::new(pos("e6"), pos("d4"), pos("h8"), 50).blocked_rays().is_empty());
+    /// ```
+    pub fn blocked_rays(&self) -> Vec<Direction> {
+        self.white_queen_position
+            .queen_distance(&self.white_king_position)
+            .ok()
+            .and_then(|(_, direction)| Direction::from_delta(direction))
+            .into_iter()
+            .collect()
+    }
+
+    fn attack_map(&self) -> AttackMap {
+        let mut board = [[ChessBoardCell::Available; BOARD_SIZE as usize]; BOARD_SIZE as usize];
+        let last_index = usize::from(BOARD_LAST_INDEX);
+
+        // Mark attackable cells by white king
+        for board_row in &mut board[usize::from(self.white_king_position.row.saturating_sub(1))
+            ..=usize::from(self.white_king_position.row + 1).min(last_index)]
+        {
+            for cell in
+                &mut board_row[usize::from(self.white_king_position.column.saturating_sub(1))
+                    ..=usize::from(self.white_king_position.column + 1).min(last_index)]
+            {
+                *cell = ChessBoardCell::Attackable;
+            }
+        }
+        board[usize::from(self.white_king_position.row)]
+            [usize::from(self.white_king_position.column)] = ChessBoardCell::King;
+
+        // Mark attackable cells by white queen
+        for &direction in &Direction::ALL {
+            for position in self.queen_ray_squares(direction) {
+                board[usize::from(position.row)][usize::from(position.column)] =
+                    ChessBoardCell::Attackable;
             }
-            board[row][column] = ChessBoardCell::Attackable;
         }
 
-        let mut best_new_position = self.black_king_position.clone();
-        let mut best_new_position_space = 0;
+        board
+    }
 
+    fn black_king_moves(&self, board: &AttackMap) -> Vec<ChessBoardPosition> {
+        let mut moves = Vec::new();
         for row in self.black_king_position.row.saturating_sub(1)
-            ..=(self.black_king_position.row + 1).min(7)
+            ..=(self.black_king_position.row + 1).min(BOARD_LAST_INDEX)
         {
             for column in self.black_king_position.column.saturating_sub(1)
-                ..=(self.black_king_position.column + 1).min(7)
+                ..=(self.black_king_position.column + 1).min(BOARD_LAST_INDEX)
             {
                 if row == self.black_king_position.row && column == self.black_king_position.column
                 {
                     continue;
                 }
-                // TODO: implement a proper strategy!
                 if let ChessBoardCell::Available = board[usize::from(row)][usize::from(column)] {
-                    best_new_position = ChessBoardPosition { row, column };
-                    best_new_position_space = 1;
+                    moves.push(ChessBoardPosition { row, column });
                 }
             }
         }
+        moves
+    }
+
+    /// Apply the solution move and return the legal black king replies unless the game is over.
+    fn apply_white_move(
+        &mut self,
+        chess_move: &Move,
+        input: &dyn std::fmt::Display,
+    ) -> Result<Vec<ChessBoardPosition>, GameOver> {
+        if let Err(err) = self.try_apply_move(chess_move) {
+            return Err(GameOver::WrongInput {
+                kind: WrongInputKind::IllegalMove,
+                error_message: err.into(),
+                input: input.to_string(),
+            });
+        }
+        self.moves += 1;
+        self.check_overlap()?;
+        let board = self.attack_map();
+        let black_in_check = matches!(
+            board[usize::from(self.black_king_position.row)]
+                [usize::from(self.black_king_position.column)],
+            ChessBoardCell::Attackable
+        );
+        let candidates = self.black_king_moves(&board);
+        self.last_white_move = Some(Move {
+            // There is a single piece of each kind, so SAN never needs disambiguation
+            disambiguation: None,
+            claim: if !black_in_check {
+                None
+            } else if candidates.is_empty() {
+                Some(MoveClaim::Checkmate)
+            } else {
+                Some(MoveClaim::Check)
+            },
+            ..*chess_move
+        });
+        // The only capture ends the game, so a check is the only progress to reset the counter
+        self.moves_since_progress = if black_in_check {
+            0
+        } else {
+            self.moves_since_progress + 1
+        };
+        if self.config.log_fen {
+            info!(target: "game_fen", "{}", self.fen(true));
+        }
+
+        if self.config.require_check_announcement {
+            let error_message = match (chess_move.claim, black_in_check) {
+                (None, true) => Some("check was not announced"),
+                (Some(MoveClaim::Check), false) => Some("no check when claimed"),
+                _ => None,
+            };
+            if let Some(error_message) = error_message {
+                return Err(GameOver::WrongInput {
+                    kind: WrongInputKind::FalseClaim,
+                    error_message: error_message.into(),
+                    input: input.to_string(),
+                });
+            }
+        }
+
+        // The check runs after either piece moved: a king move can hang the queen as well
+        let (_, black_king_to_queen) = self.black_king_proximity();
+        if black_king_to_queen == 1
+            && self
+                .white_king_position
+                .king_distance(&self.white_queen_position)
+                != 1
+        {
+            debug!("White queen moved too close to the black king without white king protection");
+            if chess_move.claim == Some(MoveClaim::Checkmate) {
+                return Err(GameOver::WrongInput {
+                    kind: WrongInputKind::FalseClaim,
+                    error_message: "no checkmate when claimed".into(),
+                    input: input.to_string(),
+                });
+            }
+            return Err(GameOver::Draw);
+        }
 
-        if best_new_position_space == 0 {
+        if candidates.is_empty() {
+            debug!(
+                "Black king at {} has no legal moves, attack map:\n{}",
+                self.black_king_position,
+                render_attack_map(&board)
+            );
             if let ChessBoardCell::Available = board[usize::from(self.black_king_position.row)]
                 [usize::from(self.black_king_position.column)]
             {
+                if self.config.forbid_allowing_stalemate {
+                    return Err(GameOver::WrongInput {
+                        kind: WrongInputKind::IllegalMove,
+                        error_message: "move allowed stalemate".into(),
+                        input: input.to_string(),
+                    });
+                }
                 return Err(GameOver::Stalemate);
             }
+            if chess_move.claim != Some(MoveClaim::Checkmate) {
+                return Err(GameOver::WrongInput {
+                    kind: WrongInputKind::FalseClaim,
+                    error_message: "no checkmate when expected".into(),
+                    input: input.to_string(),
+                });
+            }
             return Err(GameOver::Checkmate);
         }
 
-        self.black_king_position = best_new_position;
+        Ok(candidates)
+    }
 
-        Ok(())
+    fn move_black_king(&mut self, candidates: &[ChessBoardPosition]) {
+        self.last_reply_forced = Some(candidates.len() == 1);
+        let strategy = Arc::clone(&self.strategy);
+        self.black_king_position = strategy.choose(self, candidates);
+        if self.config.log_fen {
+            info!(target: "game_fen", "{}", self.fen(false));
+        }
+        let position_key = self.position_key();
+        *Arc::make_mut(&mut self.position_counts)
+            .entry(position_key)
+            .or_insert(0) += 1;
+    }
+}
+
+/// Convert the position into a [`shakmaty::Setup`] with black to move and no castling or
+/// en passant rights, so the legality judgments can be cross-checked with `shakmaty`.
+///
+/// ```
+/// # #[cfg(feature = "shakmaty")]
+/// # {
+/// use std::convert::TryFrom;
+/// use std::str::FromStr;
+/// use chess_interactor::{Chess, ChessBoardPosition};
+/// use shakmaty::{CastlingMode, FromSetup, Position};
+/// let pos = |s| ChessBoardPosition::from_str(s).unwrap();
+/// let setup = shakmaty::Setup::try_from(&Chess::new(pos("b6"), pos("c7"), pos("a8"), 50)).unwrap();
+/// assert_eq!(setup.turn, shakmaty::Color::Black);
+/// let position = shakmaty::Chess::from_setup(setup, CastlingMode::Standard).unwrap();
+/// assert_eq!(position.legal_moves().len(), 0);
+/// assert!(!position.is_check());
+///
+/// assert!(shakmaty::Setup::try_from(&Chess::new(pos("b6"), pos("b6"), pos("a8"), 50)).is_err());
+/// # }
+/// ```
+#[cfg(feature = "shakmaty")]
+impl std::convert::TryFrom<&Chess> for shakmaty::Setup {
+    type Error = &'static str;
+
+    fn try_from(chess: &Chess) -> Result<Self, Self::Error> {
+        if chess.white_king_position == chess.white_queen_position
+            || chess.white_king_position == chess.black_king_position
+            || chess.white_queen_position == chess.black_king_position
+        {
+            return Err("pieces share a square");
+        }
+        let square = |position: ChessBoardPosition| {
+            shakmaty::Square::from_coords(
+                shakmaty::File::new(u32::from(position.column)),
+                shakmaty::Rank::new(u32::from(position.row)),
+            )
+        };
+        let mut setup = shakmaty::Setup::empty();
+        setup.board.set_piece_at(
+            square(chess.white_king_position),
+            shakmaty::Color::White.king(),
+        );
+        setup.board.set_piece_at(
+            square(chess.white_queen_position),
+            shakmaty::Color::White.queen(),
+        );
+        setup.board.set_piece_at(
+            square(chess.black_king_position),
+            shakmaty::Color::Black.king(),
+        );
+        setup.turn = shakmaty::Color::Black;
+        Ok(setup)
     }
 }
@@ -1,12 +1,50 @@
-use std::str::FromStr;
-
 use tracing::info;
 
-use chess_interactor::{Chess, ChessBoardPosition, GameOver};
+use chess_interactor::{Chess, GameConfig, GameOver, InitialState, TestlibLog, WrongInputKind};
 
 const EXIT_CODE_OK: i32 = 0;
 const EXIT_CODE_WA: i32 = 1;
 const EXIT_CODE_PE: i32 = 2;
+/// The interactor itself reached an inconsistent position.
+const EXIT_CODE_FAIL: i32 = 3;
+/// The test itself is broken (e.g. a malformed answer.txt), not the solution.
+const EXIT_CODE_SETUP_ERROR: i32 = 4;
+
+fn setup() -> Result<Chess, String> {
+    #[cfg(feature = "config-file")]
+    {
+        if let Some(game_config_path) = std::env::var_os("GAME_CONFIG") {
+            let game_config = std::fs::File::open(&game_config_path).map_err(|error| {
+                format!(
+                    "unable to open the game config {:?}: {}",
+                    game_config_path, error
+                )
+            })?;
+            return chess_interactor::ProblemConfig::from_reader(std::io::BufReader::new(
+                game_config,
+            ))
+            .and_then(chess_interactor::ProblemConfig::into_chess)
+            .map_err(|error| format!("{:?}: {}", game_config_path, error));
+        }
+    }
+
+    let game_initial_state: InitialState = std::fs::read_to_string("answer.txt")
+        .map_err(|error| format!("unable to read answer.txt: {}", error))?
+        .parse()
+        .map_err(|error| {
+            format!(
+                "unable to parse initial chess piece positions from answer.txt: {}",
+                error
+            )
+        })?;
+
+    Ok(Chess::new(
+        game_initial_state.white_king,
+        game_initial_state.white_queen,
+        game_initial_state.black_king,
+        50,
+    ))
+}
 
 fn main() {
     tracing_subscriber::fmt()
@@ -17,36 +55,64 @@ fn main() {
 
     info!("Initializing Chess interactor");
 
-    let game_initial_state =
-        std::fs::read_to_string("answer.txt").expect("unable to read answer.txt");
-    let mut game_initial_state = game_initial_state.split_ascii_whitespace().map(|position| {
-        ChessBoardPosition::from_str(position)
-            .expect("unable to parse initial chess piece positions")
-    });
-    let white_king_position = game_initial_state
-        .next()
-        .expect("unable to find the initial white king position");
-    let white_queen_position = game_initial_state
-        .next()
-        .expect("unable to find the initial white queen position");
-    let black_king_position = game_initial_state
-        .next()
-        .expect("unable to find the initial black king position");
-
-    let mut chess = Chess::new(
-        white_king_position,
-        white_queen_position,
-        black_king_position,
-        50,
-    );
+    let mut chess = match setup() {
+        Ok(chess) => chess,
+        Err(error) => {
+            eprintln!("Chess interactor setup failed: {}", error);
+            std::process::exit(EXIT_CODE_SETUP_ERROR);
+        }
+    };
 
-    let game_status = chess.play();
-    info!("{:?}. Moves: {}", game_status, chess.moves());
+    if let Some(testlib_log_path) = std::env::var_os("TESTLIB_LOG") {
+        let testlib_log = match std::fs::File::create(&testlib_log_path) {
+            Ok(testlib_log) => testlib_log,
+            Err(error) => {
+                eprintln!(
+                    "Chess interactor setup failed: unable to create the testlib log {:?}: {}",
+                    testlib_log_path, error
+                );
+                std::process::exit(EXIT_CODE_SETUP_ERROR);
+            }
+        };
+        chess.set_config(GameConfig {
+            testlib_log: Some(TestlibLog::new(testlib_log)),
+            ..chess.config().clone()
+        });
+    }
+
+    let game_status = match std::env::var_os("REPLAY_MOVES") {
+        Some(replay_moves_path) => {
+            info!("Replaying the solution moves from {:?}", replay_moves_path);
+            let replay_moves = match std::fs::File::open(&replay_moves_path) {
+                Ok(replay_moves) => replay_moves,
+                Err(error) => {
+                    eprintln!(
+                        "Chess interactor setup failed: unable to open the replay moves file {:?}: {}",
+                        replay_moves_path, error
+                    );
+                    std::process::exit(EXIT_CODE_SETUP_ERROR);
+                }
+            };
+            let stdout = std::io::stdout();
+            chess.play_with(std::io::BufReader::new(replay_moves), stdout.lock())
+        }
+        None => chess.play(),
+    };
+    let game_result = chess.finish(game_status);
+    info!("{}", game_result);
 
-    let exit_code = match game_status {
-        GameOver::Checkmate => EXIT_CODE_OK,
-        GameOver::WrongInput { .. } => EXIT_CODE_PE,
-        GameOver::TooManyMoves | GameOver::Draw | GameOver::Stalemate => EXIT_CODE_WA,
+    let exit_code = if game_result.solver_succeeded {
+        EXIT_CODE_OK
+    } else {
+        match game_result.outcome {
+            GameOver::WrongInput {
+                kind: WrongInputKind::Unparseable,
+                ..
+            }
+            | GameOver::SolverDisconnected => EXIT_CODE_PE,
+            GameOver::PiecesOverlap { .. } => EXIT_CODE_FAIL,
+            _ => EXIT_CODE_WA,
+        }
     };
     std::process::exit(exit_code);
 }
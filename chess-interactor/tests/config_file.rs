@@ -0,0 +1,64 @@
+#![cfg(feature = "config-file")]
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+const EXIT_CODE_SETUP_ERROR: i32 = 4;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("chess-interactor-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn game_config_takes_precedence_over_answer() {
+    let dir = test_dir("game-config");
+    std::fs::write(dir.join("answer.txt"), "a2 h8 a4\n").unwrap();
+    std::fs::write(
+        dir.join("game.json"),
+        r#"{"initial_state": "b6 c2 a8", "config": {"labeled_initial_state": true}}"#,
+    )
+    .unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_chess-interactor"))
+        .current_dir(&dir)
+        .env_remove("REPLAY_MOVES")
+        .env("GAME_CONFIG", dir.join("game.json"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"Qc1\nQc7\nQa7#\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(output.stdout, b"WK=b6 WQ=c2 BK=a8\nKb8\nKa8\n");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn malformed_game_config_is_a_setup_error() {
+    let dir = test_dir("malformed-game-config");
+    std::fs::write(dir.join("game.json"), r#"{"initial_state": "b6 c2"}"#).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_chess-interactor"))
+        .current_dir(&dir)
+        .env_remove("REPLAY_MOVES")
+        .env("GAME_CONFIG", dir.join("game.json"))
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(EXIT_CODE_SETUP_ERROR));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("invalid initial state: unable to find the initial black king position"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
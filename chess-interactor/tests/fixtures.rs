@@ -0,0 +1,103 @@
+use std::ffi::OsStr;
+use std::path::Path;
+use std::str::FromStr;
+
+use chess_interactor::{Chess, ChessBoardPosition, InitialState, Move};
+
+/// A recorded game: the start position, the solution moves, and the expected black king replies
+/// (`-` where the game ended instead) and outcome.
+struct Fixture {
+    start: InitialState,
+    moves_limit: u64,
+    moves: Vec<Move>,
+    replies: Vec<Option<ChessBoardPosition>>,
+    outcome: String,
+}
+
+fn parse_fixture(contents: &str) -> Result<Fixture, String> {
+    let mut start = None;
+    let mut moves_limit = None;
+    let mut moves = None;
+    let mut replies = None;
+    let mut outcome = None;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("expected `key: value`, got {:?}", line))?;
+        let value = value.trim();
+        match key {
+            "start" => start = Some(InitialState::from_str(value)?),
+            "moves_limit" => {
+                moves_limit = Some(value.parse().map_err(|_| "invalid moves_limit")?);
+            }
+            "moves" => {
+                moves = Some(
+                    value
+                        .split_whitespace()
+                        .map(Move::from_str)
+                        .collect::<Result<Vec<_>, _>>()?,
+                );
+            }
+            "replies" => {
+                replies = Some(
+                    value
+                        .split_whitespace()
+                        .map(|reply| match reply {
+                            "-" => Ok(None),
+                            _ => ChessBoardPosition::from_str(reply).map(Some),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                );
+            }
+            "outcome" => outcome = Some(value.to_owned()),
+            _ => return Err(format!("unknown key {:?}", key)),
+        }
+    }
+    Ok(Fixture {
+        start: start.ok_or("missing start")?,
+        moves_limit: moves_limit.ok_or("missing moves_limit")?,
+        moves: moves.ok_or("missing moves")?,
+        replies: replies.ok_or("missing replies")?,
+        outcome: outcome.ok_or("missing outcome")?,
+    })
+}
+
+#[test]
+fn fixtures_replay_as_recorded() {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut paths: Vec<_> = std::fs::read_dir(&fixtures_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some(OsStr::new("txt")))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no fixtures in {:?}", fixtures_dir);
+
+    for path in paths {
+        let fixture = parse_fixture(&std::fs::read_to_string(&path).unwrap())
+            .unwrap_or_else(|error| panic!("{:?}: {}", path, error));
+        let mut chess = Chess::new(
+            fixture.start.white_king,
+            fixture.start.white_queen,
+            fixture.start.black_king,
+            fixture.moves_limit,
+        );
+        let (outcome, transcript) = chess.play_line(&fixture.moves);
+
+        let replies: Vec<_> = transcript.iter().map(|ply| ply.black_reply).collect();
+        assert_eq!(replies, fixture.replies, "{:?}: black king replies", path);
+        let outcome = format!("{:?}", outcome);
+        let outcome_variant = outcome.split(|c: char| !c.is_alphanumeric()).next();
+        assert_eq!(
+            outcome_variant,
+            Some(fixture.outcome.as_str()),
+            "{:?}: outcome {}",
+            path,
+            outcome
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+const EXIT_CODE_SETUP_ERROR: i32 = 4;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("chess-interactor-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn malformed_answer_is_a_setup_error() {
+    let dir = test_dir("malformed-answer");
+    std::fs::write(dir.join("answer.txt"), "a2 h9 a4\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_chess-interactor"))
+        .current_dir(&dir)
+        .env_remove("REPLAY_MOVES")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(EXIT_CODE_SETUP_ERROR));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid row"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn missing_answer_is_a_setup_error() {
+    let dir = test_dir("missing-answer");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_chess-interactor"))
+        .current_dir(&dir)
+        .env_remove("REPLAY_MOVES")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(EXIT_CODE_SETUP_ERROR));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unable to read answer.txt"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}